@@ -0,0 +1,137 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+
+use crate::{
+    command_types::{RestoreArgs, StorageKind},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    output::report,
+    storage,
+    table::{header, new_table},
+};
+
+/// Snapshots kept when `backups` isn't set in the config.
+pub const DEFAULT_BACKUPS: usize = 5;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// A copy of the data file taken before it was overwritten.
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: DateTime<Utc>,
+}
+
+impl Snapshot {
+    /// The timestamp part of the file name, which `arx restore` accepts.
+    pub fn name(&self) -> String {
+        self.taken_at.format(TIMESTAMP_FORMAT).to_string()
+    }
+}
+
+/// How often SQLite stores are snapshotted at most. Copying the whole database on every
+/// save would undo the point of writing only the changed rows.
+const SQLITE_SNAPSHOT_INTERVAL: TimeDelta = TimeDelta::hours(1);
+
+/// Snapshots of `data_path`, newest first.
+pub fn snapshots(data_path: &Path) -> Result<Vec<Snapshot>> {
+    let Some(dir) = data_path.parent().filter(|dir| dir.exists()) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.", file_name(data_path));
+    let mut snapshots: Vec<Snapshot> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let timestamp = name.strip_prefix(&prefix)?.strip_suffix(".bak")?;
+            let taken_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+            Some(Snapshot { taken_at: taken_at.and_utc(), path })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.taken_at));
+    Ok(snapshots)
+}
+
+fn snapshot_path(data_path: &Path, timestamp: &str) -> PathBuf {
+    data_path.with_file_name(format!("{}.{timestamp}.bak", file_name(data_path)))
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or("bookmarks.json")
+}
+
+impl BookmarkStore {
+    /// Copies the current data file to a new snapshot next to it and deletes all but the
+    /// newest ones. JSON stores are snapshotted on every save, SQLite ones once an hour
+    /// unless `force` is set. Does nothing if there is no data file yet or `backups` is 0.
+    pub fn snapshot(&mut self, config: &Config, force: bool) -> Result<()> {
+        let keep = config.backups.unwrap_or(DEFAULT_BACKUPS);
+        if keep == 0 || !self.path.exists() {
+            return Ok(());
+        }
+        let recent = snapshots(&self.path)?
+            .first()
+            .is_some_and(|s| Utc::now() - s.taken_at < SQLITE_SNAPSHOT_INTERVAL);
+        if !force && recent && StorageKind::of(&self.path) == StorageKind::Sqlite {
+            return Ok(());
+        }
+        let taken_at = Utc::now().format(TIMESTAMP_FORMAT);
+        self.storage.snapshot(&snapshot_path(&self.path, &taken_at.to_string()))?;
+        for old in snapshots(&self.path)?.into_iter().skip(keep) {
+            fs::remove_file(old.path)?;
+        }
+        Ok(())
+    }
+
+    pub fn restore(&mut self, args: RestoreArgs, config: &Config) -> Result<()> {
+        let snapshots = snapshots(&self.path)?;
+        let Some(wanted) = args.snapshot.filter(|_| !args.list) else {
+            return list_snapshots(&snapshots, config);
+        };
+
+        // either the number shown by `--list` or the snapshot name
+        let snapshot = match wanted.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).and_then(|i| snapshots.get(i)),
+            Err(_) => snapshots.iter().find(|s| s.name() == wanted),
+        }
+        .ok_or_else(|| Error::SnapshotNotFound(wanted.clone()))?;
+
+        (self.next_id, self.bookmarks) =
+            storage::read_snapshot(StorageKind::of(&self.path), &snapshot.path)?;
+        // the current file becomes a snapshot itself, so a restore can be undone
+        self.snapshot(config, true)?;
+        self.write()?;
+        report(
+            config.output,
+            &self.bookmarks.iter().collect::<Vec<&Bookmark>>(),
+            Some(&format!(
+                "Restored {} bookmark(s) from the snapshot taken {}",
+                self.bookmarks.len(),
+                snapshot.taken_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+            )),
+        )
+    }
+}
+
+fn list_snapshots(snapshots: &[Snapshot], config: &Config) -> Result<()> {
+    if snapshots.is_empty() {
+        println!("No snapshots yet, they are taken each time your bookmarks are saved.");
+        return Ok(());
+    }
+    let mut table = new_table(config);
+    table.set_header(header(&["#", "taken", "name"]));
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        table.add_row(vec![
+            (index + 1).to_string(),
+            snapshot.taken_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string(),
+            snapshot.name(),
+        ]);
+    }
+    println!("{table}");
+    println!("Restore one with `arx restore <#>` or `arx restore <name>`");
+    Ok(())
+}
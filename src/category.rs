@@ -0,0 +1,243 @@
+use std::{ffi::OsStr, fmt, str::FromStr};
+
+use clap::{
+    builder::{PossibleValue, TypedValueParser},
+    error::{ContextKind, ContextValue, ErrorKind},
+};
+use comfy_table::Color;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    config::Config,
+    errors::{Error, Result},
+};
+
+/// Name of a bookmark's category, one of the categories in the config.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Category(String);
+
+impl Category {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Category {
+    fn default() -> Self {
+        Category(OTHER.to_string())
+    }
+}
+
+impl FromStr for Category {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_lowercase();
+        if name.is_empty() {
+            return Err(Error::CategoryParseError(s.to_string()));
+        }
+        Ok(Category(name))
+    }
+}
+
+// older stores saved the variants of the former enum, e.g. "Book" for "book"
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A category as set up in `config.toml`:
+///
+/// ```toml
+/// [[categories]]
+/// name = "paper"
+/// color = "cyan"
+/// aliases = ["papers", "pdf"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+/// Bookmarks without a category end up here, so it always exists.
+const OTHER: &str = "other";
+
+/// Categories used when the config doesn't list any.
+const BUILT_IN: [(&str, &str); 7] = [
+    ("book", "green"),
+    ("article", "magenta"),
+    ("topic", "blue"),
+    ("project", "red"),
+    ("tool", "5"),
+    ("course", "yellow"),
+    (OTHER, "white"),
+];
+
+impl Config {
+    /// The configured categories or the built-in ones, always including "other".
+    pub fn categories(&self) -> Vec<CategoryConfig> {
+        let mut categories = if self.categories.is_empty() {
+            BUILT_IN
+                .iter()
+                .map(|(name, color)| CategoryConfig {
+                    name: name.to_string(),
+                    color: Some(color.to_string()),
+                    aliases: Vec::new(),
+                })
+                .collect()
+        } else {
+            self.categories.clone()
+        };
+        if !categories.iter().any(|c| c.name == OTHER) {
+            let other = CategoryConfig { name: OTHER.to_string(), color: None, aliases: vec![] };
+            categories.push(other);
+        }
+        categories
+    }
+
+    /// Looks up a category by its name or one of its aliases, ignoring case.
+    pub fn category(&self, name: &str) -> Result<Category> {
+        let categories = self.categories();
+        let wanted = name.to_lowercase();
+        categories
+            .iter()
+            .find(|c| {
+                c.name.to_lowercase() == wanted
+                    || c.aliases.iter().any(|a| a.to_lowercase() == wanted)
+            })
+            .map(|c| Category(c.name.to_lowercase()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+                Error::UnknownCategory(name.to_string(), names.join(", "))
+            })
+    }
+
+    pub fn category_color(&self, category: &Category) -> Color {
+        self.categories()
+            .iter()
+            .find(|c| c.name.to_lowercase() == category.name())
+            .and_then(|c| c.color.as_deref())
+            .and_then(parse_color)
+            .unwrap_or(Color::White)
+    }
+
+    /// Checks the colors and names of the configured categories.
+    pub fn validate_categories(&self) -> Result<()> {
+        for category in &self.categories {
+            if category.name.trim().is_empty() {
+                return Err(Error::CategoryParseError(category.name.clone()));
+            }
+            if let Some(color) = &category.color
+                && parse_color(color).is_none()
+            {
+                return Err(Error::InvalidColor(color.clone(), category.name.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A color name like "cyan" or "dark_blue", an ANSI color number or a hex code like "#ff8800".
+fn parse_color(color: &str) -> Option<Color> {
+    let color = color.trim().to_lowercase();
+    if let Some(hex) = color.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() == 6)?;
+        return Some(Color::Rgb { r: (value >> 16) as u8, g: (value >> 8) as u8, b: value as u8 });
+    }
+    if let Ok(number) = color.parse::<u8>() {
+        return Some(Color::AnsiValue(number));
+    }
+    Some(match color.replace(['-', ' '], "_").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        "dark_red" => Color::DarkRed,
+        "dark_green" => Color::DarkGreen,
+        "dark_yellow" => Color::DarkYellow,
+        "dark_blue" => Color::DarkBlue,
+        "dark_magenta" => Color::DarkMagenta,
+        "dark_cyan" => Color::DarkCyan,
+        "dark_grey" | "dark_gray" => Color::DarkGrey,
+        _ => return None,
+    })
+}
+
+/// Parses `--category` values with `Config::category`, so aliases and other casings become
+/// the category's name and case is folded the same way everywhere.
+#[derive(Clone)]
+struct CategoryParser {
+    config: Config,
+}
+
+impl TypedValueParser for CategoryParser {
+    type Value = Category;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> std::result::Result<Category, clap::Error> {
+        let name = value.to_string_lossy();
+        self.config.category(&name).map_err(|_| {
+            let mut err = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
+            if let Some(arg) = arg {
+                err.insert(ContextKind::InvalidArg, ContextValue::String(arg.to_string()));
+            }
+            err.insert(ContextKind::InvalidValue, ContextValue::String(name.into_owned()));
+            let names = self.config.categories().into_iter().map(|c| c.name.to_lowercase());
+            err.insert(ContextKind::ValidValue, ContextValue::Strings(names.collect()));
+            err
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        let values = self.config.categories().into_iter().map(|c| {
+            PossibleValue::new(c.name.to_lowercase()).aliases(c.aliases)
+        });
+        Some(Box::new(values.collect::<Vec<_>>().into_iter()))
+    }
+}
+
+/// Makes every `--category` argument accept the configured categories and their aliases,
+/// which also lists them in `--help`.
+pub fn with_categories(cli: clap::Command, config: &Config) -> clap::Command {
+    let parser = CategoryParser { config: config.clone() };
+
+    let subcommands: Vec<String> =
+        cli.get_subcommands().map(|s| s.get_name().to_string()).collect();
+    subcommands.iter().fold(cli, |cli, name| {
+        cli.mut_subcommand(name, |subcommand| {
+            let ids: Vec<String> = subcommand
+                .get_arguments()
+                .map(|arg| arg.get_id().to_string())
+                .filter(|id| id == "category" || id == "filter_category")
+                .collect();
+            ids.iter().fold(subcommand, |subcommand, id| {
+                subcommand.mut_arg(id, |arg| arg.value_parser(parser.clone()))
+            })
+        })
+    })
+}
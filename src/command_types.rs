@@ -1,17 +1,87 @@
 use std::{fmt, path::PathBuf, str::FromStr};
 
+use chrono::NaiveDate;
 use clap::{Parser, Subcommand};
-use comfy_table::{Color, presets};
+use comfy_table::presets;
 use serde::{Deserialize, Serialize};
 
+use crate::category::Category;
+use crate::data::Scope;
 use crate::errors::{Error, Result};
+use crate::filter::Filter;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Parser, Debug)]
 #[command(version = "0.1.0")]
 #[command(about = "A cli archive for all your bookmarks that you will totally read")]
 pub struct CLI {
     #[command(subcommand)]
     pub command: Subcommands,
+
+    #[arg(short, long, global = true, value_enum, help = "output format")]
+    pub output: Option<OutputFormat>,
+
+    #[arg(long, global = true, conflicts_with = "output", help = "shorthand for --output json")]
+    pub json: bool,
+
+    #[arg(
+        long,
+        global = true,
+        env = "ARX_CONFIG",
+        help = "config file to use instead of the default one"
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "ARX_DATA",
+        help = "directory to keep the bookmarks in, overrides the save location"
+    )]
+    pub data_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        global = true,
+        env = "ARX_PROFILE",
+        help = "profile to use instead of the current one"
+    )]
+    pub profile: Option<String>,
+}
+
+impl CLI {
+    /// The clap command, with `edit` selecting by `--in-category` and `--with-status` since
+    /// its `--category` and `--status` set those fields.
+    pub fn command() -> clap::Command {
+        <CLI as clap::CommandFactory>::command().mut_subcommand("edit", |edit| {
+            edit.mut_arg("filter_category", |arg| {
+                arg.long("in-category").help("select bookmarks in this category")
+            })
+            .mut_arg("filter_status", |arg| {
+                arg.long("with-status").help("select bookmarks with this status")
+            })
+        })
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        match self.json {
+            true => OutputFormat::Json,
+            false => self.output.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Default, PartialEq)]
+pub enum OutputFormat {
+    /// human readable tables and messages
+    #[default]
+    Table,
+    /// a json array of bookmarks
+    Json,
+    /// one json bookmark per line
+    Jsonl,
+    /// tab separated bookmark fields with a header line
+    Tsv,
 }
 
 #[derive(Subcommand, Debug)]
@@ -22,6 +92,9 @@ pub enum Subcommands {
     #[clap(about = "list bookmarks (alias: ls)", alias = "ls")]
     List(ListArgs),
 
+    #[clap(about = "search titles, notes, tags and urls")]
+    Search(SearchArgs),
+
     #[clap(about = "remove bookmark (alias: rm, del, delete)", aliases = ["rm", "del", "delete"])]
     Remove(RemoveArgs),
 
@@ -37,8 +110,86 @@ pub enum Subcommands {
     #[clap(name = "copy-url", about = "copy bookmark url (alias: cp)", alias = "cp")]
     CopyUrl(CopyUrlArgs),
 
+    #[clap(about = "export bookmarks to markdown, html, csv, netscape or json")]
+    Export(ExportArgs),
+
+    #[clap(about = "import bookmarks from a browser export")]
+    Import(ImportArgs),
+
+    #[clap(about = "undo the last change to your bookmarks or config")]
+    Undo(UndoArgs),
+
+    #[clap(about = "redo the last undone change")]
+    Redo(UndoArgs),
+
+    #[clap(about = "restore bookmarks from an automatic snapshot")]
+    Restore(RestoreArgs),
+
     #[clap(name = "config", about = "configure arx")]
     Config(ConfigArgs),
+
+    #[clap(about = "manage profiles, each with bookmarks of its own")]
+    Profile(ProfileArgs),
+
+    #[clap(name = "migrate-storage", about = "move your bookmarks to another storage backend")]
+    MigrateStorage(MigrateStorageArgs),
+}
+
+impl Subcommands {
+    /// The bookmarks the command reads, only the named ones when it names them all by ID
+    /// and only the matching ones when `--where` alone chooses them.
+    pub fn scope(&self) -> Scope {
+        match self {
+            Subcommands::Add(_) => Scope::Ids(Vec::new()),
+            Subcommands::List(ListArgs { filter, .. })
+            | Subcommands::Export(ExportArgs { filter, .. }) => {
+                filter.expr.clone().map_or(Scope::All, Scope::Matching)
+            }
+            Subcommands::Remove(RemoveArgs { select })
+            | Subcommands::Done(DoneArgs { select })
+            | Subcommands::Open(OpenArgs { select }) => select.scope(),
+            Subcommands::Edit(args) => args.select.scope(),
+            Subcommands::CopyUrl(CopyUrlArgs { query: SearchQuery::Id(id), .. }) => {
+                Scope::Ids(vec![*id])
+            }
+            _ => Scope::All,
+        }
+    }
+
+    /// The `--where` expression of the command, if it takes one and it is given.
+    pub fn filter_mut(&mut self) -> Option<&mut Filter> {
+        match self {
+            Subcommands::List(args) => args.filter.expr.as_mut(),
+            Subcommands::Export(args) => args.filter.expr.as_mut(),
+            Subcommands::Remove(RemoveArgs { select })
+            | Subcommands::Done(DoneArgs { select })
+            | Subcommands::Open(OpenArgs { select }) => select.expr.as_mut(),
+            Subcommands::Edit(args) => args.select.expr.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Whether the command changes the store and needs it locked exclusively.
+    pub fn writes(&self) -> bool {
+        match self {
+            Subcommands::Add(_)
+            | Subcommands::Remove(_)
+            | Subcommands::Edit(_)
+            | Subcommands::Done(_)
+            | Subcommands::Import(_)
+            | Subcommands::Undo(_)
+            | Subcommands::Redo(_)
+            | Subcommands::Restore(_)
+            | Subcommands::Config(_)
+            | Subcommands::Profile(_)
+            | Subcommands::MigrateStorage(_) => true,
+            Subcommands::List(_)
+            | Subcommands::Search(_)
+            | Subcommands::Open(_)
+            | Subcommands::CopyUrl(_)
+            | Subcommands::Export(_) => false,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -49,7 +200,7 @@ pub struct AddArgs {
     #[arg(short, long, help = "add bookmark url")]
     pub url: Option<String>,
 
-    #[arg(short, long, help = "add bookmark category", value_enum)]
+    #[arg(short, long, help = "add bookmark category")]
     pub category: Option<Category>,
 
     #[arg(short, long, num_args=1.., value_delimiter = ' ', help = "add bookmark tags")]
@@ -63,14 +214,47 @@ pub struct AddArgs {
 
     #[arg(long, help = "hide bookmark by default")]
     pub hidden: bool,
+
+    #[arg(long, value_enum, help = "add the priority of bookmark")]
+    pub priority: Option<Priority>,
+
+    #[arg(long, help = "add the date to finish bookmark by, e.g. 2025-06-30")]
+    pub due: Option<NaiveDate>,
 }
 
-#[derive(Debug, Clone, clap::ValueEnum, Serialize, Deserialize, Default, PartialEq)]
+#[derive(
+    Debug, Clone, clap::ValueEnum, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Ord,
+)]
 pub enum Status {
     #[default]
     None,
     Pending,
+    Reading,
+    Paused,
     Done,
+    Abandoned,
+    Archived,
+}
+
+impl Status {
+    /// Plain name of the status, as accepted on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::None => "none",
+            Status::Pending => "pending",
+            Status::Reading => "reading",
+            Status::Paused => "paused",
+            Status::Done => "done",
+            Status::Abandoned => "abandoned",
+            Status::Archived => "archived",
+        }
+    }
+
+    /// Whether the bookmark is finished with one way or another, these are left out of
+    /// lists and searches by default.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Status::Done | Status::Abandoned | Status::Archived)
+    }
 }
 
 impl fmt::Display for Status {
@@ -78,64 +262,39 @@ impl fmt::Display for Status {
         match self {
             Status::None => write!(f, "━━"),
             Status::Pending => write!(f, "pending"),
+            Status::Reading => write!(f, "reading"),
+            Status::Paused => write!(f, "paused"),
             Status::Done => write!(f, "DONE"),
+            Status::Abandoned => write!(f, "abandoned"),
+            Status::Archived => write!(f, "archived"),
         }
     }
 }
 
-#[derive(Debug, Clone, clap::ValueEnum, Serialize, Deserialize, Default, PartialEq)]
-pub enum Category {
-    Book,
-    Article,
-    Topic,
-    Project,
-    Tool,
-    Course,
-    #[default]
-    Other,
+#[derive(
+    Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
 }
 
-impl FromStr for Category {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_str() {
-            "book" => Ok(Category::Book),
-            "article" => Ok(Category::Article),
-            "topic" => Ok(Category::Topic),
-            "project" => Ok(Category::Project),
-            "tool" => Ok(Category::Tool),
-            "course" => Ok(Category::Course),
-            _ => Err(Error::CategoryParseError(s.to_string())),
-        }
-    }
-}
-
-impl From<&Category> for Color {
-    fn from(category: &Category) -> Self {
-        match category {
-            Category::Book => Color::Green,
-            Category::Article => Color::Magenta,
-            Category::Topic => Color::Blue,
-            Category::Project => Color::Red,
-            Category::Tool => Color::AnsiValue(5),
-            Category::Course => Color::Yellow,
-            Category::Other => Color::White,
+impl Priority {
+    /// Plain name of the priority, as accepted on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
         }
     }
 }
 
-impl fmt::Display for Category {
+impl fmt::Display for Priority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Category::Book => write!(f, "book"),
-            Category::Article => write!(f, "article"),
-            Category::Topic => write!(f, "topic"),
-            Category::Project => write!(f, "project"),
-            Category::Tool => write!(f, "tool"),
-            Category::Course => write!(f, "course"),
-            Category::Other => write!(f, "other"),
-        }
+        write!(f, "{}", self.name())
     }
 }
 
@@ -146,40 +305,289 @@ pub enum ListFields {
     Hidden,
 }
 
+/// A column of the `arx list` table.
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Id,
+    Title,
+    Category,
+    Tags,
+    Status,
+    Url,
+    Created,
+    Started,
+    Completed,
+    Priority,
+    Due,
+    Notes,
+}
+
+impl Column {
+    /// Shown when neither `--columns` nor the config choose the columns.
+    pub const DEFAULT: [Column; 4] = [Column::Id, Column::Title, Column::Category, Column::Status];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Title => "name",
+            Column::Category => "category",
+            Column::Tags => "tags",
+            Column::Status => "status",
+            Column::Url => "url",
+            Column::Created => "created",
+            Column::Started => "started",
+            Column::Completed => "completed",
+            Column::Priority => "priority",
+            Column::Due => "due",
+            Column::Notes => "notes",
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 pub struct ListArgs {
-    #[arg(short, long, help = "filter by category")]
-    pub category: Option<String>,
+    #[command(flatten)]
+    pub filter: FilterArgs,
 
-    #[arg(short, long, help = "filter by tag")]
-    pub tag: Option<String>,
+    #[arg(short, long, help = "show all flags (e.g. done/hidden too)")]
+    pub all: bool,
 
     #[arg(short, long, help = "set page to show")]
     pub page: Option<usize>,
 
-    #[arg(short, long, help = "show all flags (e.g. done/hidden too)")]
-    pub all: bool,
+    #[arg(long, help = "bookmarks per page or 'auto' to fit the terminal, overrides the config")]
+    pub page_by: Option<PageBy>,
+
+    #[arg(long, conflicts_with_all = ["page", "page_by"], help = "show all bookmarks at once")]
+    pub no_page: bool,
+
+    #[arg(long, help = "print whole urls instead of clickable links")]
+    pub full_urls: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "columns to show e.g. 'id,title,tags,created', defaults to the configured ones"
+    )]
+    pub columns: Option<Vec<Column>>,
+
+    #[arg(long, value_enum, help = "order to list bookmarks in, defaults to the configured one")]
+    pub sort: Option<SortKey>,
+
+    #[arg(short, long, help = "reverse the order")]
+    pub reverse: bool,
 
     #[arg(value_enum)]
     pub fields: Option<ListFields>,
 }
 
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    Id,
+    Title,
+    /// newest last, use --reverse for newest first
+    Created,
+    Category,
+    Status,
+    /// highest first, bookmarks without one last
+    Priority,
+    /// soonest first, bookmarks without a due date last
+    Due,
+}
+
+/// Rows per page of `arx list`, a fixed number or as many as fit the terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageBy {
+    Rows(usize),
+    Auto,
+}
+
+impl FromStr for PageBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(PageBy::Auto);
+        }
+        s.parse()
+            .map(PageBy::Rows)
+            .map_err(|_| format!("'{s}' is neither a number nor 'auto'"))
+    }
+}
+
+// stored as a plain number or "auto" in the config file
+impl Serialize for PageBy {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            PageBy::Rows(rows) => serializer.serialize_u64(*rows as u64),
+            PageBy::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PageBy {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Rows(usize),
+            Name(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Rows(rows) => Ok(PageBy::Rows(rows)),
+            Repr::Name(name) => name.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Parser, Debug, Default)]
+pub struct FilterArgs {
+    #[arg(short, long, help = "filter by category")]
+    pub category: Option<Category>,
+
+    #[arg(short, long, help = "filter by tag")]
+    pub tag: Option<String>,
+
+    #[arg(short, long, help = "filter by status", value_enum)]
+    pub status: Option<Status>,
+
+    #[arg(
+        short = 'w',
+        long = "where",
+        value_name = "EXPR",
+        help = "filter expression e.g. 'tag:rust and not status:done'"
+    )]
+    pub expr: Option<Filter>,
+
+    /// Keeps done and hidden bookmarks in, set from the command's own flags.
+    #[arg(skip)]
+    pub all: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct SearchArgs {
+    #[arg(required = true, help = "words to look for, every word has to match somewhere")]
+    pub query: Vec<String>,
+
+    #[arg(short, long, help = "search done/hidden bookmarks too")]
+    pub all: bool,
+
+    #[arg(short = 'n', long, help = "show at most this many results")]
+    pub limit: Option<usize>,
+}
+
 #[derive(Parser, Debug)]
 pub struct RemoveArgs {
+    #[command(flatten)]
+    pub select: SelectionArgs,
+}
+
+/// Bookmarks chosen by IDs, ID ranges, fuzzy queries and filters, for the commands that
+/// work on several bookmarks at once.
+#[derive(Parser, Debug)]
+pub struct SelectionArgs {
     #[arg(
-        required = true,
-        help = "remove bookmark by ID or fuzzy search query e.g. '123' or 'my query'",
-        value_name = "ID | query"
+        help = "bookmark IDs, ID ranges or fuzzy search queries e.g. '12', '3-9' or 'my query'",
+        value_name = "ID | range | query"
+    )]
+    pub queries: Vec<SearchQuery>,
+
+    #[arg(long, help = "select bookmarks with this tag")]
+    pub tag: Option<String>,
+
+    #[arg(long = "category", id = "filter_category", help = "select bookmarks in this category")]
+    pub category: Option<Category>,
+
+    #[arg(
+        long = "status",
+        id = "filter_status",
+        value_enum,
+        help = "select bookmarks with this status"
+    )]
+    pub status: Option<Status>,
+
+    #[arg(
+        short = 'w',
+        long = "where",
+        value_name = "EXPR",
+        help = "select bookmarks matching a filter expression e.g. 'tag:rust and category:book'"
     )]
-    pub list: Vec<SearchQuery>,
+    pub expr: Option<Filter>,
+
+    #[arg(short, long, help = "select done/hidden bookmarks too")]
+    pub all: bool,
+
+    #[arg(long, help = "only show the bookmarks that would be affected")]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub pick: PickArgs,
+}
+
+impl SelectionArgs {
+    /// The bookmarks the selection can choose from, see `Subcommands::scope`.
+    fn scope(&self) -> Scope {
+        if self.queries.is_empty() {
+            return self.expr.clone().map_or(Scope::All, Scope::Matching);
+        }
+        if self.has_filter() {
+            return Scope::All;
+        }
+        let ids: Option<Vec<usize>> = self
+            .queries
+            .iter()
+            .map(|query| match query {
+                SearchQuery::Id(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        ids.map_or(Scope::All, Scope::Ids)
+    }
+
+    pub fn has_filter(&self) -> bool {
+        self.tag.is_some()
+            || self.category.is_some()
+            || self.status.is_some()
+            || self.expr.is_some()
+    }
+}
+
+#[derive(Parser, Debug, Default, Clone, Copy)]
+pub struct PickArgs {
+    #[arg(long, conflicts_with = "strict", help = "use the best fuzzy match without asking")]
+    pub first: bool,
+
+    #[arg(long, help = "fail when a fuzzy query matches several bookmarks")]
+    pub strict: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum SearchQuery {
     Id(usize),
+    /// inclusive range of IDs, e.g. `3-9`
+    Range(usize, usize),
     Query(String),
 }
 
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchQuery::Id(id) => write!(f, "{id}"),
+            SearchQuery::Range(start, end) => write!(f, "{start}-{end}"),
+            SearchQuery::Query(query) => write!(f, "{query}"),
+        }
+    }
+}
+
 impl FromStr for SearchQuery {
     type Err = String;
 
@@ -187,6 +595,12 @@ impl FromStr for SearchQuery {
         // Try parsing as u64 first
         if let Ok(id) = s.parse::<usize>() {
             Ok(SearchQuery::Id(id))
+        } else if let Some((start, end)) = s
+            .split_once('-')
+            .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+            .filter(|(start, end)| start <= end)
+        {
+            Ok(SearchQuery::Range(start, end))
         } else {
             // If not a number, treat as query string
             Ok(SearchQuery::Query(s.to_string()))
@@ -196,49 +610,93 @@ impl FromStr for SearchQuery {
 
 #[derive(Parser, Debug)]
 pub struct EditArgs {
+    #[command(flatten)]
+    pub select: SelectionArgs,
+
     #[arg(
-        required = true,
-        help = "edit bookmark data",
-        long_help = "edit bookmark data by id or fuzzy search query e.g. '123' or 'my query'",
-        value_name = "ID | query"
+        long,
+        short,
+        conflicts_with_all = [
+            "title", "url", "notes", "category", "status", "hidden", "priority", "due", "tags",
+            "no_url", "no_notes", "no_priority", "no_due", "no_tags", "add_tag", "remove_tag",
+        ],
+        help = "edit all fields in $EDITOR"
     )]
-    pub query: SearchQuery,
+    pub interactive: bool,
 
-    #[arg(long, short)]
+    #[arg(long = "set-title", short, visible_alias = "title")]
     pub title: Option<String>,
-    #[arg(long, short)]
+    #[arg(long = "set-url", short, visible_alias = "url")]
     pub url: Option<String>,
-    #[arg(long, short)]
+    #[arg(long = "set-notes", short, visible_alias = "notes")]
     pub notes: Option<String>,
-    #[arg(long, short)]
+    #[arg(long = "set-category", short, visible_alias = "category")]
     pub category: Option<Category>,
-    #[arg(long, short)]
+    #[arg(long = "set-status", short, visible_alias = "status")]
     pub status: Option<Status>,
-    #[arg(long)]
+    #[arg(long = "set-hidden", visible_alias = "hidden")]
     pub hidden: Option<bool>,
-    #[arg(long)]
+    #[arg(long = "set-priority", value_enum, visible_alias = "priority")]
+    pub priority: Option<Priority>,
+    #[arg(long = "set-due", visible_alias = "due")]
+    pub due: Option<NaiveDate>,
+    #[arg(
+        long = "set-tags",
+        visible_alias = "tags",
+        num_args=1..,
+        value_delimiter = ' ',
+        help = "replace all tags"
+    )]
     pub tags: Option<Vec<String>>,
+
+    #[arg(long, conflicts_with = "url", help = "remove the url")]
+    pub no_url: bool,
+    #[arg(long, conflicts_with = "notes", help = "remove the notes")]
+    pub no_notes: bool,
+    #[arg(long, conflicts_with = "priority", help = "remove the priority")]
+    pub no_priority: bool,
+    #[arg(long, conflicts_with = "due", help = "remove the due date")]
+    pub no_due: bool,
+    #[arg(long, conflicts_with = "tags", help = "remove all tags")]
+    pub no_tags: bool,
+    #[arg(long, num_args=1.., value_delimiter = ' ', help = "add tags to the existing ones")]
+    pub add_tag: Vec<String>,
+    #[arg(long, num_args=1.., value_delimiter = ' ', help = "remove tags from the existing ones")]
+    pub remove_tag: Vec<String>,
+}
+
+impl EditArgs {
+    /// Whether any field would be changed.
+    pub fn has_changes(&self) -> bool {
+        self.title.is_some()
+            || self.url.is_some()
+            || self.notes.is_some()
+            || self.category.is_some()
+            || self.status.is_some()
+            || self.hidden.is_some()
+            || self.priority.is_some()
+            || self.due.is_some()
+            || self.tags.is_some()
+            || self.no_url
+            || self.no_notes
+            || self.no_priority
+            || self.no_due
+            || self.no_tags
+            || !self.add_tag.is_empty()
+            || !self.remove_tag.is_empty()
+    }
 }
 
 #[derive(Parser, Debug)]
 pub struct DoneArgs {
-    #[arg(
-        required = true,
-        help = "mark bookmark as done",
-        long_help = "mark bookmark as done by id or fuzzy search query e.g. '123' or 'my query'",
-        value_name = "ID | query"
-    )]
-    pub query: SearchQuery,
+    #[command(flatten)]
+    pub select: SelectionArgs,
 }
 
 #[derive(Parser, Debug)]
 pub struct OpenArgs {
-    #[arg(
-        required = true,
-        help = "open bookmark url by id or fuzzy search query e.g. '123' or 'my query'",
-        value_name = "ID | query"
-    )]
-    pub query: SearchQuery,
+    #[command(flatten)]
+    pub select: SelectionArgs,
 }
 
 #[derive(Parser, Debug)]
@@ -250,9 +708,143 @@ pub struct CopyUrlArgs {
         value_name = "ID | query"
     )]
     pub query: SearchQuery,
+
+    #[command(flatten)]
+    pub pick: PickArgs,
 }
 
-#[derive(Clone, clap::ValueEnum, Debug, Deserialize, Serialize)]
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    #[arg(short, long, value_enum, help = "format to export to")]
+    pub format: ExportFormat,
+
+    #[arg(help = "file to write to, prints to stdout if not set")]
+    pub file: Option<PathBuf>,
+
+    /// Done and hidden bookmarks are exported too, unless the filters leave them out.
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// markdown list grouped by category
+    Md,
+    /// standalone html page grouped by category
+    Html,
+    Csv,
+    /// bookmarks.html that browsers can import
+    Netscape,
+    Json,
+}
+
+#[derive(Parser, Debug)]
+pub struct ImportArgs {
+    #[arg(help = "file to import")]
+    pub file: PathBuf,
+
+    #[arg(short, long, value_enum, default_value = "netscape", help = "format of the file")]
+    pub format: ImportFormat,
+
+    #[arg(short, long, help = "category of the imported bookmarks")]
+    pub category: Option<Category>,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+pub enum ImportFormat {
+    /// bookmarks.html as exported by browsers
+    Netscape,
+}
+
+#[derive(Parser, Debug)]
+pub struct UndoArgs {
+    #[arg(short, long, help = "don't ask for confirmation")]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommand {
+    #[clap(about = "list profiles (alias: ls)", alias = "ls")]
+    List,
+
+    #[clap(about = "create a profile")]
+    Create(ProfileCreateArgs),
+
+    #[clap(about = "use a profile from now on, 'default' goes back to the main bookmarks")]
+    Switch {
+        name: String,
+    },
+
+    #[clap(about = "delete a profile and its bookmarks (alias: rm)", alias = "rm")]
+    Delete {
+        name: String,
+
+        #[arg(short, long, help = "don't ask for confirmation")]
+        yes: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct ProfileCreateArgs {
+    pub name: String,
+
+    #[arg(short, long, help = "where to keep its bookmarks, next to the main ones by default")]
+    pub save_location: Option<PathBuf>,
+
+    #[arg(short, long, help = "table style of the profile")]
+    pub table_style: Option<TableStyle>,
+
+    #[arg(short, long, help = "bookmarks per page of the profile or 'auto'")]
+    pub page_by: Option<PageBy>,
+
+    #[arg(long, help = "switch to the profile right away")]
+    pub switch: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct MigrateStorageArgs {
+    #[arg(long, value_enum, help = "backend to keep the bookmarks in")]
+    pub to: StorageKind,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "what to do when bookmarks of that backend already exist, asks by default"
+    )]
+    pub on_existing: Option<ExistingStore>,
+}
+
+/// How bookmarks are kept on disk.
+#[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// one json file, rewritten on every change
+    #[default]
+    Json,
+    /// an sqlite database, changes only touch their bookmarks
+    Sqlite,
+}
+
+#[derive(Parser, Debug)]
+pub struct RestoreArgs {
+    #[arg(
+        help = "snapshot to restore, by number or name",
+        long_help = "snapshot to restore, by its number or name as shown by --list. Lists the snapshots if not set",
+        value_name = "# | name"
+    )]
+    pub snapshot: Option<String>,
+
+    #[arg(short, long, help = "list snapshots")]
+    pub list: bool,
+}
+
+#[derive(Clone, clap::ValueEnum, Debug, Deserialize, Serialize, PartialEq)]
 #[clap(rename_all = "snake_case")]
 pub enum TableStyle {
     AsciiFull,
@@ -294,22 +886,77 @@ impl TableStyle {
 #[clap(group(
     clap::ArgGroup::new("config-args")
         .required(true)
-        .args(&["save-location", "table-style", "page-by"])
+        .args(&[
+            "save-location", "table-style", "page-by", "backups", "sort", "sort-reverse",
+            "full-urls", "columns",
+        ])
 ))]
 pub struct ConfigArgs {
     #[arg(long, short, name = "save-location")]
     pub save_location: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_enum,
+        requires = "save-location",
+        help = "what to do when the new save location already has bookmarks, asks by default"
+    )]
+    pub on_existing: Option<ExistingStore>,
+
     #[arg(long, short, name = "table-style")]
     pub table_style: Option<TableStyle>,
 
-    #[arg(long, short, name = "page-by")]
-    pub page_by: Option<usize>,
+    #[arg(long, short, name = "page-by", help = "bookmarks per page or 'auto' to fit the terminal")]
+    pub page_by: Option<PageBy>,
+
+    #[arg(long, short, name = "backups", help = "number of snapshots to keep, 0 turns them off")]
+    pub backups: Option<usize>,
+
+    #[arg(long, name = "sort", help = "default order of `arx list`")]
+    pub sort: Option<SortKey>,
+
+    #[arg(long, name = "sort-reverse", help = "reverse the default order of `arx list`")]
+    pub sort_reverse: Option<bool>,
+
+    #[arg(
+        long,
+        name = "full-urls",
+        help = "print whole urls for terminals without clickable links"
+    )]
+    pub full_urls: Option<bool>,
+
+    #[arg(
+        long,
+        name = "columns",
+        value_enum,
+        value_delimiter = ',',
+        help = "default columns of `arx list` e.g. 'id,title,tags,status'"
+    )]
+    pub columns: Option<Vec<Column>>,
+}
+
+/// How `arx config --save-location` treats bookmarks already saved at the new location.
+#[derive(Debug, Clone, Copy, clap::ValueEnum, PartialEq)]
+pub enum ExistingStore {
+    /// keep both, adding the bookmarks found there to yours
+    Merge,
+    /// replace them with yours, a snapshot of them is kept
+    Overwrite,
+    /// leave everything as it is
+    Abort,
 }
 
 impl ConfigArgs {
     pub fn validate(&self) -> Result<()> {
-        if self.save_location.is_none() && self.table_style.is_none() && self.page_by.is_none() {
+        if self.save_location.is_none()
+            && self.table_style.is_none()
+            && self.page_by.is_none()
+            && self.backups.is_none()
+            && self.sort.is_none()
+            && self.sort_reverse.is_none()
+            && self.full_urls.is_none()
+            && self.columns.is_none()
+        {
             return Err(Error::NoConfigArgs);
         }
         Ok(())
@@ -1,398 +1,407 @@
 use crate::command_types::{
-    AddArgs, ConfigArgs, CopyUrlArgs, DoneArgs, EditArgs, ListArgs, OpenArgs, RemoveArgs,
-    SearchQuery,
+    AddArgs, Column, ConfigArgs, CopyUrlArgs, DoneArgs, EditArgs, FilterArgs, ListArgs, OpenArgs,
+    OutputFormat, PageBy, RemoveArgs, SortKey,
 };
-use crate::config::{Config, load_config};
-use crate::data::{Arx, Bookmark};
+use crate::config::Config;
+use crate::data::Bookmark;
 use crate::{
     BookmarkStore, Cell, Error, ListFields, Status,
     errors::Result,
-    utils::{copy, fuzz},
+    output::report,
+    utils::{copy, prompt},
 };
-use comfy_table::{
-    Attribute, CellAlignment, Color, ColumnConstraint, Table, Width, presets::UTF8_FULL,
-};
-use std::fs;
-use std::io::{self, Write};
-use terminal_link::Link;
+use crate::select::{Selection, selection_table};
+use crate::table::{column_cell, columns_table, rows_fitting};
+use std::{cmp::Reverse, io::IsTerminal};
 
-impl Arx {
-    pub fn init() -> Result<Arx> {
-        let config = load_config()?;
-        let store = BookmarkStore::load(&config)?;
-        Ok(Arx { store, config })
-    }
-}
+/// Bookmarks per page when `page_by` isn't set in the config.
+const DEFAULT_PAGE_BY: usize = 10;
 
 impl BookmarkStore {
-    pub fn add(&mut self, args: AddArgs) -> Result<()> {
+    pub fn add(&mut self, args: AddArgs, config: &Config) -> Result<()> {
         let id = self.next_id;
-        let new_bookmark = Bookmark {
+        let mut new_bookmark = Bookmark {
             id,
             title: args.title,
             url: args.url,
             category: args.category.unwrap_or_default(),
             tags: args.tags,
             notes: args.notes,
-            status: args.status.unwrap_or_default(),
+            status: Status::None,
             hidden: args.hidden,
             created_at: chrono::Utc::now(),
+            completed_at: None,
+            history: Vec::new(),
+            priority: args.priority,
+            due: args.due,
         };
+        new_bookmark.set_status(args.status.unwrap_or_default());
         self.bookmarks.push(new_bookmark);
         self.next_id += 1;
-        self.save()?;
-        println!("Bookmark with ID #{} successfully added!", self.next_id - 1);
-        Ok(())
+        self.save(config)?;
+        report(
+            config.output,
+            &[self.get(id)?],
+            Some(&format!("Bookmark with ID #{id} successfully added!")),
+        )
     }
 
-    pub fn list(&mut self, mut args: ListArgs, config: &Config) -> Result<()> {
-        if self.bookmarks.is_empty() {
+    pub fn list(&self, args: ListArgs, config: &Config) -> Result<()> {
+        // filter if a field is specified, e.g. only entries with urls/notes/etc.
+        let filter = FilterArgs { all: args.all, ..args.filter };
+        let mut bookmarks = self.filtered(&filter, args.fields.as_ref())?;
+        // --reverse flips whichever order applies, including a reversed default
+        let reverse = match args.sort {
+            Some(_) => args.reverse,
+            None => args.reverse != config.sort_reverse.unwrap_or(false),
+        };
+        sort(&mut bookmarks, args.sort.or(config.sort).unwrap_or(SortKey::Id), reverse);
+        if config.output != OutputFormat::Table {
+            return report(config.output, &bookmarks, None);
+        }
+
+        // with --where only the matching bookmarks were read
+        if self.bookmarks.is_empty() && filter.expr.is_none() {
             println!("You have no bookmarks yet...");
             return Ok(());
         }
+        if bookmarks.is_empty() {
+            println!("No bookmarks found.");
+            return Ok(());
+        }
 
-        // filter if a field is specified, e.g. only entries with urls/notes/etc.
-        self.filter_args(&mut args)?;
-
-        let mut table = Table::new();
-        table.load_preset(
-            config
-                .table_style
-                .as_ref()
-                .map(|s| s.to_comfy_style())
-                .unwrap_or(UTF8_FULL),
-        );
-
-        // Initialize headers and calculate column widths
-        let mut headers = vec![Cell::new("ID"), Cell::new("name")];
-        let column_widths: Vec<(usize, usize)> = match args.fields {
-            Some(ListFields::Urls) => {
-                headers.push(Cell::new("url"));
-                vec![(1, 65), (2, 6)]
-            }
-            Some(ListFields::Notes) => {
-                headers.push(Cell::new("notes"));
-                vec![(1, 21), (2, 50)]
-            }
-            Some(ListFields::Hidden) | None => {
-                headers.extend(vec![Cell::new("category"), Cell::new("status")]);
-                vec![(1, 50), (2, 10), (3, 10)]
+        // links would end up as escape codes in pipes and files
+        let full_urls = args.full_urls
+            || config.full_urls.unwrap_or(false)
+            || !std::io::stdout().is_terminal();
+        // `arx ls urls` and `arx ls notes` are shortcuts for showing that column
+        let columns: Vec<Column> = match (args.columns, args.fields) {
+            (Some(columns), _) => columns,
+            (None, Some(ListFields::Urls)) => vec![Column::Id, Column::Title, Column::Url],
+            (None, Some(ListFields::Notes)) => vec![Column::Id, Column::Title, Column::Notes],
+            (None, Some(ListFields::Hidden) | None) => {
+                config.columns.clone().unwrap_or_else(|| Column::DEFAULT.to_vec())
             }
         };
+        let mut table = columns_table(config, &columns, full_urls);
+        let row = |bookmark: &Bookmark| -> Vec<Cell> {
+            columns
+                .iter()
+                .map(|column| column_cell(*column, bookmark, config, full_urls))
+                .collect()
+        };
 
-        headers = headers
-            .into_iter()
-            .map(|cell| cell.fg(Color::Yellow).add_attribute(Attribute::Bold))
-            .collect();
-
-        // Build header row w/ column widths
-        table.set_header(headers);
-        let id_col = table.column_mut(0).expect("id column exists");
-        id_col.set_constraint(ColumnConstraint::Absolute(Width::Fixed(5)));
-        let name_col = table.column_mut(1).expect("name column exists");
-        if args.fields != Some(ListFields::Notes) {
-            name_col.set_cell_alignment(CellAlignment::Left);
-        } else {
-            name_col.set_cell_alignment(CellAlignment::Center);
-        }
-        for (index, width) in column_widths {
-            let column = table.column_mut(index).expect("col should exist");
-            column.set_constraint(ColumnConstraint::Absolute(Width::Fixed(width as u16)));
-            if index > 1 {
-                column.set_cell_alignment(CellAlignment::Center);
-            }
-        }
-
-        // Calculate rows
-        let page = match args.page {
-            Some(0) => 1,
-            Some(page) => page,
-            None => 1,
+        // Paginate what is left after filtering and sorting
+        let page_by = args.page_by.or(config.page_by());
+        let page_by = match page_by.unwrap_or(PageBy::Rows(DEFAULT_PAGE_BY)) {
+            _ if args.no_page => bookmarks.len(),
+            PageBy::Rows(0) => return Err(Error::ZeroPagination),
+            PageBy::Rows(rows) => rows,
+            // not a terminal, e.g. piped into a pager
+            PageBy::Auto => rows_fitting(&table, row(bookmarks[0])).unwrap_or(bookmarks.len()),
         };
-        let paginate_by = config.page_by.unwrap_or(10);
-        if self.bookmarks.len() <= (page - 1) * paginate_by {
+        let pages = bookmarks.len().div_ceil(page_by);
+        let page = args.page.unwrap_or(1).max(1);
+        if page > pages {
             return Err(Error::PageNotFound(page));
         }
+        let page_rows = &bookmarks[(page - 1) * page_by..(page * page_by).min(bookmarks.len())];
+        for bookmark in page_rows {
+            table.add_row(row(bookmark));
+        }
 
-        for bookmark in self
-            .bookmarks
-            .iter_mut()
-            .skip((page - 1) * paginate_by)
-            .take(page * paginate_by)
-        {
-            let mut row = vec![
-                Cell::new(bookmark.id),
-                if bookmark.status == Status::Pending {
-                    Cell::new(&bookmark.title)
-                        .fg(Color::White)
-                        .add_attribute(Attribute::Bold)
-                        .set_alignment(CellAlignment::Left)
-                } else {
-                    Cell::new(&bookmark.title).set_alignment(CellAlignment::Left)
-                },
-            ];
-            match args.fields {
-                Some(ListFields::Urls) => {
-                    row.push(
-                        Cell::new(
-                            bookmark
-                                .url
-                                .clone()
-                                .map(|_url| "[XX]".to_string())
-                                .unwrap_or_else(|| "━━".to_string()),
-                        )
-                        .fg(Color::DarkBlue),
-                    );
-                }
-                Some(ListFields::Notes) => row.push(
-                    Cell::new(bookmark.notes.clone().unwrap_or("-".to_string()))
-                        .set_alignment(CellAlignment::Left),
-                ),
-                Some(ListFields::Hidden) | None => row.extend(vec![
-                    Cell::new(&bookmark.category).fg((&bookmark.category).into()),
-                    if bookmark.status == Status::Done {
-                        Cell::new(&bookmark.status).fg(Color::Green)
-                    } else if bookmark.status == Status::Pending {
-                        Cell::new(&bookmark.status).add_attribute(Attribute::Italic)
-                    } else {
-                        Cell::new(&bookmark.status)
-                    },
-                ]),
-            };
-            table.add_row(row);
+        println!("{table}");
+        if pages > 1 {
+            println!("Showing page {page} out of {pages} (specify with -p <num>)");
         }
+        Ok(())
+    }
 
-        let mut table = table.to_string();
-        if args.fields == Some(ListFields::Urls) {
-            let mut lines: Vec<String> = table.lines().map(String::from).collect();
-            for (id, bookmark) in self.bookmarks.iter().enumerate() {
-                if let Some(url) = &bookmark.url {
-                    let link = Link::new("LINK", url).to_string();
-                    let line_id = 3 + 2 * id;
-                    lines[line_id] = lines[line_id].replace("[XX]", &link);
-                }
-            }
-            table = lines.join("\n");
+    pub fn remove(&mut self, args: RemoveArgs, config: &Config) -> Result<()> {
+        let selection = self.select(&args.select, config)?;
+        if args.select.dry_run {
+            return self.preview(&selection, config);
         }
-        if table.lines().count() <= 3 {
-            println!("No bookmarks found.");
+        if selection.unconfirmed && !self.confirm_removal(&selection, config)? {
             return Ok(());
         }
 
-        println!("{table}");
-        println!(
-            "Showing page {page} out of {} (specify with -p <num>)",
-            self.bookmarks.len().div_ceil(paginate_by)
-        );
-        Ok(())
+        let mut removed = Vec::new();
+        for id in &selection.ids {
+            let index = self.position(*id)?;
+            let bookmark = self.bookmarks.remove(index);
+            if config.output == OutputFormat::Table {
+                println!("Successfully removed #{id} - {}", short_title(&bookmark.title));
+            }
+            removed.push(bookmark);
+        }
+        self.save(config)?;
+        report(config.output, &removed.iter().collect::<Vec<_>>(), None)
     }
 
-    pub fn remove(&mut self, args: RemoveArgs) -> Result<()> {
-        for arg in args.list {
-            match arg {
-                SearchQuery::Id(id) => match self.bookmarks.iter().position(|e| e.id == id) {
-                    Some(id) => {
-                        let title = &mut self.bookmarks[id].title.clone();
-                        if title.len() > 24 {
-                            title.truncate(21);
-                            title.push_str("...");
-                        }
-                        let _ = self.bookmarks.remove(id);
-                        println!("Successfully removed #{id} - {}", title);
-                    }
-                    None => {
-                        return Err(Error::IDNotFound(id));
-                    }
-                },
-                SearchQuery::Query(query) => {
-                    let id = fuzz(&query, &self.bookmarks);
-                    let title = &mut self.bookmarks[id].title.clone();
-                    if title.len() > 24 {
-                        title.truncate(21);
-                        title.push_str("...");
-                    }
-                    print!("Confirm removing '{}' from your bookmarks [y/n] ", title);
-                    io::stdout().flush()?;
-
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input)?;
-                    let input = input.trim().to_lowercase();
-                    if input == "y" {
-                        let _ = self.bookmarks.remove(id);
-                    }
-                }
-            };
-        }
-        self.normalize();
-        self.save()?;
-        Ok(())
+    fn confirm_removal(&self, selection: &Selection, config: &Config) -> Result<bool> {
+        let bookmarks = self.selected(selection)?;
+        let question = match bookmarks.as_slice() {
+            [bookmark] => format!(
+                "Confirm removing '{}' from your bookmarks [y/n] ",
+                short_title(&bookmark.title)
+            ),
+            _ => {
+                eprintln!("{}", selection_table(&bookmarks, config));
+                format!("Confirm removing these {} bookmarks [y/n] ", bookmarks.len())
+            }
+        };
+        Ok(prompt(&question)?.eq_ignore_ascii_case("y"))
     }
 
-    pub fn edit(&mut self, args: EditArgs) -> Result<()> {
-        if args.category.is_none()
-            && args.hidden.is_none()
-            && args.notes.is_none()
-            && args.status.is_none()
-            && args.tags.is_none()
-            && args.title.is_none()
-            && args.url.is_none()
-        {
+    pub fn edit(&mut self, args: EditArgs, config: &Config) -> Result<()> {
+        if !args.interactive && !args.has_changes() {
             return Err(Error::NoEditSpecified);
         }
-        let id = match args.query {
-            SearchQuery::Id(id) => self
-                .bookmarks
-                .iter()
-                .find_map(|b| if b.id == id { Some(b.id) } else { None })
-                .ok_or(Error::IDNotFound(id))?,
-            SearchQuery::Query(query) => fuzz(&query, &self.bookmarks),
-        };
+        let selection = self.select(&args.select, config)?;
+        if args.select.dry_run {
+            return self.preview(&selection, config);
+        }
+        if args.interactive {
+            let [id] = selection.ids[..] else {
+                return Err(Error::InteractiveEditOfMany(selection.ids.len()));
+            };
+            return self.edit_interactive(id, config);
+        }
 
-        let bookmark = &mut self.bookmarks[id];
-        if let Some(category) = args.category {
-            bookmark.category = category;
-        } else if let Some(hidden) = args.hidden {
-            bookmark.hidden = hidden;
-        } else if let Some(notes) = args.notes {
-            bookmark.notes = Some(notes);
-        } else if let Some(status) = args.status {
-            bookmark.status = status;
-        } else if let Some(tags) = args.tags {
-            bookmark.tags = Some(tags);
-        } else if let Some(title) = args.title {
-            bookmark.title = title;
-        } else if let Some(url) = args.url {
-            bookmark.url = Some(url);
+        for id in &selection.ids {
+            apply_edit(&args, self.get_mut(*id)?);
         }
-        self.save()?;
-        Ok(())
+        self.save(config)?;
+        let message = (selection.ids.len() > 1)
+            .then(|| format!("Edited {} bookmarks", selection.ids.len()));
+        report(config.output, &self.selected(&selection)?, message.as_deref())
     }
 
-    pub fn done(&mut self, args: DoneArgs) -> Result<()> {
-        match args.query {
-            SearchQuery::Id(id) => match self.bookmarks.iter_mut().find(|b| b.id == id) {
-                Some(bookmark) => bookmark.status = Status::Done,
-                None => return Err(Error::IDNotFound(id)),
-            },
-            SearchQuery::Query(query) => {
-                let id = fuzz(&query, &self.bookmarks);
-                self.bookmarks[id].status = Status::Done;
-            }
-        };
-
-        self.save()?;
-        Ok(())
+    pub fn done(&mut self, args: DoneArgs, config: &Config) -> Result<()> {
+        let selection = self.select(&args.select, config)?;
+        if args.select.dry_run {
+            return self.preview(&selection, config);
+        }
+        for id in &selection.ids {
+            self.get_mut(*id)?.set_status(Status::Done);
+        }
+        self.save(config)?;
+        let message = (selection.ids.len() > 1)
+            .then(|| format!("Marked {} bookmarks as done", selection.ids.len()));
+        report(config.output, &self.selected(&selection)?, message.as_deref())
     }
 
-    pub fn open(&self, args: OpenArgs) -> Result<()> {
-        match args.query {
-            SearchQuery::Id(id) => match self.bookmarks.iter().find(|b| b.id == id) {
-                Some(bookmark) => match &bookmark.url {
-                    Some(url) => open::that(url)?,
-                    None => return Err(Error::NoUrl(id)),
-                },
-                None => return Err(Error::IDNotFound(id)),
-            },
-            SearchQuery::Query(query) => {
-                let id = fuzz(&query, &self.bookmarks);
-                match &self.bookmarks[id].url {
-                    Some(url) => open::that(url)?,
-                    None => return Err(Error::NoUrl(id)),
-                }
+    pub fn open(&self, args: OpenArgs, config: &Config) -> Result<()> {
+        let selection = self.select(&args.select, config)?;
+        if args.select.dry_run {
+            return self.preview(&selection, config);
+        }
+        for bookmark in self.selected(&selection)? {
+            match &bookmark.url {
+                Some(url) => open::that(url)?,
+                None if selection.ids.len() == 1 => return Err(Error::NoUrl(bookmark.id)),
+                None => eprintln!("Skipped #{} - {}, it has no URL", bookmark.id, bookmark.title),
             }
         }
         Ok(())
     }
 
-    pub fn copy_url(&self, args: CopyUrlArgs) -> Result<()> {
-        match args.query {
-            SearchQuery::Id(id) => match self.bookmarks.iter().find(|b| b.id == id) {
-                Some(bookmark) => match &bookmark.url {
-                    Some(url) => copy(url.to_owned())?,
-                    None => return Err(Error::NoUrl(id)),
-                },
-                None => return Err(Error::IDNotFound(id)),
-            },
-            SearchQuery::Query(query) => {
-                let id = fuzz(&query, &self.bookmarks);
-                match &self.bookmarks[id].url {
-                    Some(url) => copy(url.to_owned())?,
-                    None => return Err(Error::NoUrl(id)),
-                }
-            }
+    pub fn copy_url(&self, args: CopyUrlArgs, config: &Config) -> Result<()> {
+        let id = self.resolve(&args.query, args.pick, config)?.id;
+        match &self.get(id)?.url {
+            Some(url) => copy(url.to_owned())?,
+            None => return Err(Error::NoUrl(id)),
         }
         Ok(())
     }
 
-    pub fn config(&self, args: ConfigArgs, config: &mut Config) -> Result<()> {
+    pub fn config(&mut self, args: ConfigArgs, config: &mut Config) -> Result<()> {
         args.validate()?;
         if let Some(mut path) = args.save_location {
+            if config.data_dir.is_some() {
+                return Err(Error::SaveLocationOverridden);
+            }
             if path.is_dir() {
-                path = path.join("bookmarks.json");
+                // the store keeps its file name, and with it its storage
+                path = path.join(self.path.file_name().unwrap_or("bookmarks.json".as_ref()));
+            }
+            self.relocate(std::path::absolute(path)?, config, args.on_existing)?;
+            match config.profile_config_mut() {
+                Some(profile) => profile.save_location = Some(self.path.clone()),
+                None => config.save_location = self.path.clone(),
             }
-            fs::rename(&config.save_location, &path)?;
-            config.save_location = path;
         }
-        if let Some(num) = args.page_by {
-            if num == 0 {
+        // with a profile in use these belong to the profile
+        if let Some(page_by) = args.page_by {
+            if page_by == PageBy::Rows(0) {
                 return Err(Error::ZeroPagination);
             }
-            config.page_by = Some(num);
+            match config.profile_config_mut() {
+                Some(profile) => profile.page_by = Some(page_by),
+                None => config.page_by = Some(page_by),
+            }
         }
         if let Some(style) = args.table_style {
-            config.table_style = Some(style);
+            match config.profile_config_mut() {
+                Some(profile) => profile.table_style = Some(style),
+                None => config.table_style = Some(style),
+            }
+        }
+        if let Some(num) = args.backups {
+            config.backups = Some(num);
+        }
+        if let Some(key) = args.sort {
+            config.sort = Some(key);
+        }
+        if let Some(reverse) = args.sort_reverse {
+            config.sort_reverse = Some(reverse);
+        }
+        if let Some(full) = args.full_urls {
+            config.full_urls = Some(full);
+        }
+        if let Some(columns) = args.columns {
+            config.columns = Some(columns);
         }
         self.save_config(config)?;
         Ok(())
     }
 }
 
+/// Applies the setters of `arx edit` to one bookmark.
+fn apply_edit(args: &EditArgs, bookmark: &mut Bookmark) {
+    if let Some(title) = &args.title {
+        bookmark.title = title.clone();
+    }
+    if let Some(category) = &args.category {
+        bookmark.category = category.clone();
+    }
+    if let Some(status) = &args.status {
+        bookmark.set_status(status.clone());
+    }
+    if let Some(hidden) = args.hidden {
+        bookmark.hidden = hidden;
+    }
+    if args.no_priority {
+        bookmark.priority = None;
+    } else if let Some(priority) = args.priority {
+        bookmark.priority = Some(priority);
+    }
+    if args.no_due {
+        bookmark.due = None;
+    } else if let Some(due) = args.due {
+        bookmark.due = Some(due);
+    }
+    if args.no_url {
+        bookmark.url = None;
+    } else if let Some(url) = &args.url {
+        bookmark.url = Some(url.clone());
+    }
+    if args.no_notes {
+        bookmark.notes = None;
+    } else if let Some(notes) = &args.notes {
+        bookmark.notes = Some(notes.clone());
+    }
+
+    // replacing or clearing happens first, so `--tags a b --add-tag c` ends up with all three
+    let mut tags = match (args.no_tags, &args.tags) {
+        (true, _) => Vec::new(),
+        (false, Some(tags)) => tags.clone(),
+        (false, None) => bookmark.tags.take().unwrap_or_default(),
+    };
+    tags.retain(|tag| !args.remove_tag.contains(tag));
+    for tag in &args.add_tag {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    bookmark.tags = if tags.is_empty() { None } else { Some(tags) };
+}
+
+/// Sorts bookmarks by `key` and then by ID, `reverse` flips the whole order.
+fn sort(bookmarks: &mut [&Bookmark], key: SortKey, reverse: bool) {
+    match key {
+        SortKey::Id => bookmarks.sort_by_key(|b| b.id),
+        SortKey::Title => bookmarks.sort_by_cached_key(|b| b.title.to_lowercase()),
+        SortKey::Created => bookmarks.sort_by_key(|b| b.created_at),
+        SortKey::Category => bookmarks.sort_by_cached_key(|b| b.category.to_string()),
+        SortKey::Status => bookmarks.sort_by_key(|b| b.status.clone()),
+        SortKey::Priority => bookmarks.sort_by_key(|b| (b.priority.is_none(), Reverse(b.priority))),
+        SortKey::Due => bookmarks.sort_by_key(|b| (b.due.is_none(), b.due)),
+    }
+    if reverse {
+        bookmarks.reverse();
+    }
+}
+
+/// Titles longer than 24 characters are cut for one line messages.
+fn short_title(title: &str) -> String {
+    if title.chars().count() > 24 {
+        format!("{}...", title.chars().take(21).collect::<String>())
+    } else {
+        title.to_string()
+    }
+}
+
 mod utils {
     use crate::{
-        command_types::{ListArgs, ListFields, Status},
-        data::BookmarkStore,
-        errors::Result,
+        command_types::{FilterArgs, ListFields},
+        data::{Bookmark, BookmarkStore},
+        errors::{Error, Result},
     };
 
     impl BookmarkStore {
-        pub fn filter_args(&mut self, args: &mut ListArgs) -> Result<()> {
-            match args.fields {
-                Some(ListFields::Urls) => self.bookmarks.retain(|b| b.url.is_some()),
-                Some(ListFields::Notes) => self.bookmarks.retain(|b| b.notes.is_some()),
-                Some(ListFields::Hidden) => self.bookmarks.retain(|b| b.hidden),
-                None => {}
-            }
-
-            if let Some(category) = &args.category {
-                let category = category.parse()?;
-                self.bookmarks.retain(|b| b.category == category);
-            }
-
-            if let Some(tag) = &args.tag {
-                self.bookmarks.retain(|b| b.tags.is_some());
-                self.bookmarks
-                    .retain(|b| b.tags.as_ref().unwrap().contains(tag));
-            }
+        /// Bookmarks matching the list filters, in store order. Done and hidden bookmarks
+        /// are left out unless `--all` is set or they are asked for explicitly.
+        pub fn filtered(
+            &self,
+            filter: &FilterArgs,
+            fields: Option<&ListFields>,
+        ) -> Result<Vec<&Bookmark>> {
+            let asks_for_status = filter.status.is_some()
+                || filter.expr.as_ref().is_some_and(|e| e.mentions_status());
+            let asks_for_hidden = fields == Some(&ListFields::Hidden)
+                || filter.expr.as_ref().is_some_and(|e| e.mentions_hidden());
+            Ok(self
+                .bookmarks
+                .iter()
+                .filter(|b| match fields {
+                    Some(ListFields::Urls) => b.url.is_some(),
+                    Some(ListFields::Notes) => b.notes.is_some(),
+                    Some(ListFields::Hidden) => b.hidden,
+                    None => true,
+                })
+                .filter(|b| filter.category.as_ref().is_none_or(|c| &b.category == c))
+                .filter(|b| {
+                    filter.tag.as_ref().is_none_or(|tag| {
+                        b.tags.as_ref().is_some_and(|tags| tags.contains(tag))
+                    })
+                })
+                .filter(|b| filter.status.as_ref().is_none_or(|s| &b.status == s))
+                .filter(|b| filter.expr.as_ref().is_none_or(|expr| expr.matches(b)))
+                .filter(|b| {
+                    filter.all
+                        || ((asks_for_status || !b.status.is_closed())
+                            && (asks_for_hidden || !b.hidden))
+                })
+                .collect())
+        }
 
-            if !args.all {
-                if let Some(ListFields::Hidden) = args.fields {
-                    self.bookmarks.retain(|b| b.status != Status::Done);
-                } else {
-                    self.bookmarks
-                        .retain(|b| b.status != Status::Done && !b.hidden);
-                }
-            }
+        pub fn position(&self, id: usize) -> Result<usize> {
+            self.bookmarks
+                .iter()
+                .position(|b| b.id == id)
+                .ok_or(Error::IDNotFound(id))
+        }
 
-            Ok(())
+        pub fn get(&self, id: usize) -> Result<&Bookmark> {
+            let index = self.position(id)?;
+            Ok(&self.bookmarks[index])
         }
 
-        pub fn normalize(&mut self) {
-            for id in 0..self.bookmarks.len() {
-                if self.bookmarks[id].id != id {
-                    self.bookmarks[id].id = id;
-                }
-            }
-            self.next_id = self.bookmarks.len();
+        pub fn get_mut(&mut self, id: usize) -> Result<&mut Bookmark> {
+            let index = self.position(id)?;
+            Ok(&mut self.bookmarks[index])
         }
     }
 }
@@ -1,11 +1,13 @@
-use std::{fs, path::PathBuf, sync::LazyLock};
+use std::{collections::BTreeMap, env, ffi::OsString, fs, path::PathBuf, sync::LazyLock};
 
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    command_types::TableStyle,
+    category::CategoryConfig,
+    command_types::{CLI, Column, OutputFormat, PageBy, SortKey, StorageKind, TableStyle},
     errors::{Error, Result},
+    profile::ProfileConfig,
 };
 
 #[derive(Default)]
@@ -25,32 +27,115 @@ pub static PROJECT_DIRS: LazyLock<DefaultDirs> =
         None => DefaultDirs { error: true, ..DefaultDirs::default() },
     });
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq)]
 pub struct Config {
     #[serde(default = "default_save_location")]
     pub save_location: PathBuf,
 
+    /// Profile used when `--profile` isn't given, the main bookmarks if unset.
+    pub profile: Option<String>,
+
     pub table_style: Option<TableStyle>,
 
-    pub page_by: Option<usize>,
+    pub page_by: Option<PageBy>,
+
+    pub backups: Option<usize>,
+
+    pub sort: Option<SortKey>,
+
+    pub sort_reverse: Option<bool>,
+
+    pub full_urls: Option<bool>,
+
+    pub columns: Option<Vec<Column>>,
+
+    /// Backend of new stores, `arx migrate-storage` converts an existing one.
+    pub storage: Option<StorageKind>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<CategoryConfig>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Set from the command line for each run.
+    #[serde(skip)]
+    pub output: OutputFormat,
+
+    /// The file the config was loaded from and is saved to.
+    #[serde(skip)]
+    pub config_path: PathBuf,
+
+    /// Set by `--data-dir` or `ARX_DATA`, takes the place of `save_location`.
+    #[serde(skip)]
+    pub data_dir: Option<PathBuf>,
+
+    /// The profile in use for this run, from `--profile` or `profile`.
+    #[serde(skip)]
+    pub active_profile: Option<String>,
 }
 
-pub fn load_config() -> Result<Config> {
-    let default_dirs = LazyLock::force(&PROJECT_DIRS);
-    if default_dirs.error {
-        return Err(Error::NoProjectDirs);
+impl Config {
+    /// The bookmarks file in use, the one in the data directory if it is overridden.
+    pub fn store_path(&self) -> PathBuf {
+        self.profile_path(self.active_profile.as_deref())
     }
-    match default_dirs.config_path.exists() {
+}
+
+pub const CONFIG_ENV: &str = "ARX_CONFIG";
+pub const DATA_ENV: &str = "ARX_DATA";
+
+/// Paths given with `--config` and `--data-dir` or their environment variables. They are
+/// read in a first lenient pass over the arguments, since the config decides which
+/// categories the arguments may use in the real one.
+#[derive(Default)]
+pub struct Overrides {
+    pub config_path: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Overrides {
+    pub fn from_args(args: impl IntoIterator<Item = OsString>) -> Overrides {
+        // mistakes in the arguments are left for the real parse to report
+        let matches = CLI::command().ignore_errors(true).try_get_matches_from(args).ok();
+        let path = |id: &str, env_name: &str| {
+            let given = match &matches {
+                Some(matches) => matches.get_one::<PathBuf>(id).cloned(),
+                // should even the lenient parse fail, the environment still counts
+                None => env::var_os(env_name).map(PathBuf::from),
+            };
+            given.filter(|path| !path.as_os_str().is_empty())
+        };
+        Overrides { config_path: path("config", CONFIG_ENV), data_dir: path("data_dir", DATA_ENV) }
+    }
+}
+
+pub fn load_config(overrides: &Overrides) -> Result<Config> {
+    let config_path = match &overrides.config_path {
+        Some(path) => std::path::absolute(path)?,
+        None if PROJECT_DIRS.error => return Err(Error::NoProjectDirs),
+        None => PROJECT_DIRS.config_path.clone(),
+    };
+    let mut config = match config_path.exists() {
         true => {
-            let data = fs::read_to_string(&default_dirs.config_path)?;
+            let data = fs::read_to_string(&config_path)?;
             let config: Config = toml::from_str(&data)?;
-            Ok(config)
+            config.validate_categories()?;
+            config
         }
-        false => Ok(Config {
-            save_location: default_dirs.save_location.clone(),
-            ..Config::default()
-        }),
+        false => Config { save_location: default_save_location(), ..Config::default() },
+    };
+    config.config_path = config_path;
+    config.data_dir = overrides.data_dir.as_deref().map(std::path::absolute).transpose()?;
+    if config.data_dir.is_none() && PROJECT_DIRS.error {
+        return Err(Error::NoProjectDirs);
     }
+    Ok(config)
+}
+
+/// The platform's data directory for arx.
+pub fn default_data_dir() -> PathBuf {
+    PROJECT_DIRS.save_location.parent().map(PathBuf::from).unwrap_or_default()
 }
 
 pub fn default_save_location() -> PathBuf {
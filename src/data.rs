@@ -1,32 +1,97 @@
-use std::fs;
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    command_types::{Category, Status},
-    config::{Config, PROJECT_DIRS},
-    errors::Result,
+    category::Category,
+    command_types::{Priority, Status},
+    config::Config,
+    errors::{Error, Result},
+    filter::Filter,
+    journal,
+    storage::{self, Storage},
+    utils::write_atomic,
 };
 
+/// How long to wait for another arx process to release the store.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Which bookmarks a command reads. Commands that only name bookmarks by ID or select
+/// them with `--where` read just those, so they stay quick on big SQLite stores.
+pub enum Scope {
+    All,
+    Ids(Vec<usize>),
+    /// The bookmarks matching the expression, which the command still narrows down.
+    Matching(Filter),
+}
+
 pub struct Arx {
     pub store: BookmarkStore,
     pub config: Config,
+    _lock: StoreLock,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct BookmarkStore {
-    pub next_id: usize,
-    pub bookmarks: Vec<Bookmark>,
+impl Arx {
+    /// Loads the config and the store, `exclusive` locks the store for commands that change it.
+    pub fn init(config: Config, exclusive: bool, scope: Scope) -> Result<Arx> {
+        let lock = StoreLock::acquire(&config.store_path(), exclusive)?;
+        let store = BookmarkStore::load(&config, scope)?;
+        Ok(Arx { store, config, _lock: lock })
+    }
+}
+
+/// Advisory lock on the bookmark store, released when dropped. Commands that change the
+/// store hold it exclusively from load to save, read-only commands share it.
+pub struct StoreLock {
+    _file: File,
 }
 
-impl Default for BookmarkStore {
-    fn default() -> Self {
-        BookmarkStore { next_id: 1, bookmarks: Vec::new() }
+impl StoreLock {
+    pub fn acquire(save_location: &Path, exclusive: bool) -> Result<StoreLock> {
+        let file_name = save_location.file_name().and_then(|n| n.to_str()).unwrap_or("arx");
+        let path = save_location.with_file_name(format!("{file_name}.lock"));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+
+        let started = Instant::now();
+        loop {
+            let result = match exclusive {
+                true => file.try_lock(),
+                false => file.try_lock_shared(),
+            };
+            match result {
+                Ok(()) => return Ok(StoreLock { _file: file }),
+                Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                    thread::sleep(Duration::from_millis(50))
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(Error::StoreLocked(save_location.display().to_string()));
+                }
+                Err(TryLockError::Error(err)) => return Err(err.into()),
+            }
+        }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+pub struct BookmarkStore {
+    pub next_id: usize,
+    pub bookmarks: Vec<Bookmark>,
+    /// The file the store was loaded from and is saved to.
+    pub path: PathBuf,
+    pub storage: Box<dyn Storage>,
+    /// The bookmarks as the storage has them, so saving writes only what changed since.
+    pub saved: Vec<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct Bookmark {
     pub id: usize,
     pub title: String,
@@ -37,57 +102,122 @@ pub struct Bookmark {
     pub status: Status,
     pub hidden: bool,
     pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Every status the bookmark has had, oldest first.
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// The day the bookmark should be finished by.
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
 }
 
-// #[derive(Serialize, Deserialize)]
-// struct Metadata {
-//     prev_save_location: PathBuf,
-// }
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct StatusChange {
+    pub status: Status,
+    pub at: DateTime<Utc>,
+}
+
+impl Bookmark {
+    /// Changes the status and records it in the history, finishing the bookmark
+    /// sets `completed_at` and only reopening it clears it again, so archiving a done
+    /// bookmark keeps when it was finished.
+    pub fn set_status(&mut self, status: Status) {
+        if self.status == status {
+            return;
+        }
+        let now = Utc::now();
+        if status == Status::Done {
+            self.completed_at = Some(now);
+        } else if !status.is_closed() {
+            self.completed_at = None;
+        }
+        self.history.push(StatusChange { status: status.clone(), at: now });
+        self.status = status;
+    }
+
+    /// When the bookmark was first marked as reading.
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.history.iter().find(|change| change.status == Status::Reading).map(|c| c.at)
+    }
+}
 
 impl BookmarkStore {
-    pub fn load(config: &Config) -> Result<BookmarkStore> {
-        // amend_possible_change(&save_location)?;
+    pub fn load(config: &Config, scope: Scope) -> Result<BookmarkStore> {
+        let path = config.store_path();
+        let mut storage = storage::open(&path);
+        let bookmarks = match scope {
+            Scope::All => return BookmarkStore::load_from(&path),
+            Scope::Ids(mut ids) => {
+                ids.sort_unstable();
+                ids.dedup();
+                let mut bookmarks = Vec::new();
+                for id in ids {
+                    bookmarks.extend(storage.get(id)?);
+                }
+                bookmarks
+            }
+            Scope::Matching(filter) => storage.query(Some(&filter))?,
+        };
+        Ok(BookmarkStore {
+            next_id: storage.next_id()?,
+            saved: bookmarks.clone(),
+            bookmarks,
+            path,
+            storage,
+        })
+    }
 
-        if !config.save_location.exists() {
-            return Ok(BookmarkStore::default());
-        }
-        let data = fs::read_to_string(&config.save_location)?;
-        let store: BookmarkStore = serde_json::from_str(&data)?;
-        Ok(store)
+    /// Reads the store at `path`, an empty one bound to `path` if there is no file yet.
+    pub fn load_from(path: &Path) -> Result<BookmarkStore> {
+        let mut storage = storage::open(path);
+        let (next_id, bookmarks) = storage.load()?;
+        Ok(BookmarkStore {
+            next_id,
+            saved: bookmarks.clone(),
+            bookmarks,
+            path: path.to_path_buf(),
+            storage,
+        })
+    }
+
+    /// Saves to a new file from now on, which the next save writes in full.
+    pub fn rebind(&mut self, path: PathBuf) {
+        self.storage = storage::open(&path);
+        self.saved = Vec::new();
+        self.path = path;
     }
 
-    pub fn save(&mut self) -> Result<()> {
-        let data = serde_json::to_string(&self)?;
-        if !PROJECT_DIRS.save_location.exists() {
-            fs::create_dir_all(
-                PROJECT_DIRS
-                    .save_location
-                    .parent()
-                    .expect("Root as save location"),
-            )?
+    pub fn save(&mut self, config: &Config) -> Result<()> {
+        self.snapshot(config, false)?;
+        self.write()
+    }
+
+    /// Writes the changes since the last save without taking a snapshot first.
+    pub fn write(&mut self) -> Result<()> {
+        if !self.path.exists() {
+            fs::create_dir_all(self.path.parent().expect("Root as save location"))?
         }
-        Ok(fs::write(&PROJECT_DIRS.save_location, data)?)
+        for change in journal::diff(&self.saved, &self.bookmarks) {
+            match (change.before, change.after) {
+                (None, Some(bookmark)) => self.storage.insert(&bookmark)?,
+                (Some(_), Some(bookmark)) => self.storage.update(&bookmark)?,
+                (Some(bookmark), None) => self.storage.delete(bookmark.id)?,
+                (None, None) => {}
+            }
+        }
+        self.storage.commit(self.next_id)?;
+        self.saved = self.bookmarks.clone();
+        Ok(())
     }
 
     pub fn save_config(&self, config: &mut Config) -> Result<()> {
         let data = toml::to_string(config)?;
-        if !PROJECT_DIRS.config_path.exists() {
-            fs::create_dir_all(PROJECT_DIRS.config_path.parent().unwrap())?
+        if !config.config_path.exists() {
+            fs::create_dir_all(config.config_path.parent().unwrap())?
         }
-        Ok(fs::write(&PROJECT_DIRS.config_path, data)?)
+        write_atomic(&config.config_path, data.as_bytes())
     }
 }
-
-// fn amend_possible_change(save_location: &PathBuf) -> Result<()> {
-//     if PROJECT_DIRS.metadata.exists() {
-//         let data = fs::read_to_string(&PROJECT_DIRS.metadata)?;
-//         let metadata: Metadata = toml::from_str(&data)?;
-//         if &metadata.prev_save_location != save_location {}
-//     } else {
-//         fs::create_dir_all(PROJECT_DIRS.metadata.parent().unwrap())?;
-//         let metadata = Metadata { prev_save_location: PROJECT_DIRS.data_path.clone() };
-//         let data = toml::to_string(&metadata)?;
-//         fs::write(&PROJECT_DIRS.metadata, data)?;
-//     }
-//     Ok(())
-// }
@@ -0,0 +1,204 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command_types::{Priority, Status},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    output::report,
+    utils::{prompt, value_names},
+};
+
+/// The editable fields of a bookmark as they appear in the editor.
+#[derive(Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    title: String,
+    #[serde(default)]
+    url: String,
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    status: String,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default)]
+    priority: String,
+    #[serde(default)]
+    due: String,
+    #[serde(default)]
+    notes: String,
+}
+
+impl Document {
+    fn from_bookmark(bookmark: &Bookmark) -> Document {
+        Document {
+            title: bookmark.title.clone(),
+            url: bookmark.url.clone().unwrap_or_default(),
+            category: bookmark.category.to_string(),
+            tags: bookmark.tags.clone().unwrap_or_default(),
+            status: bookmark.status.name().to_string(),
+            hidden: bookmark.hidden,
+            priority: bookmark.priority.map(Priority::name).unwrap_or_default().to_string(),
+            due: bookmark.due.map(|due| due.to_string()).unwrap_or_default(),
+            notes: bookmark.notes.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Checks the edited values and writes them to `bookmark`.
+    fn apply(self, bookmark: &mut Bookmark, config: &Config) -> Result<()> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err(Error::InvalidEdit("title can't be empty".to_string()));
+        }
+        bookmark.category = config
+            .category(self.category.trim())
+            .map_err(|err| Error::InvalidEdit(err.to_string()))?;
+        bookmark.set_status(value_of("status", &self.status)?);
+        bookmark.title = title.to_string();
+        bookmark.url = non_empty(self.url.trim());
+        bookmark.notes = non_empty(self.notes.trim_end());
+        bookmark.hidden = self.hidden;
+        bookmark.priority = match self.priority.trim() {
+            "" => None,
+            priority => Some(value_of("priority", priority)?),
+        };
+        bookmark.due = match self.due.trim() {
+            "" => None,
+            due => Some(NaiveDate::parse_from_str(due, "%Y-%m-%d").map_err(|_| {
+                Error::InvalidEdit(format!("due date '{due}' isn't a YYYY-MM-DD date"))
+            })?),
+        };
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        bookmark.tags = if tags.is_empty() { None } else { Some(tags) };
+        Ok(())
+    }
+}
+
+impl BookmarkStore {
+    /// Opens the bookmark with ID `id` in `$VISUAL` or `$EDITOR` as a TOML document and
+    /// saves whatever comes back, asking to edit again while it doesn't parse.
+    pub fn edit_interactive(&mut self, id: usize, config: &Config) -> Result<()> {
+        let original = Document::from_bookmark(self.get(id)?);
+        let mut buffer = format!(
+            "# Editing bookmark #{id}, save and close the editor to apply the changes.\n\
+             # category: {}\n\
+             # status: {}\n\
+             # priority: {}, or empty\n\
+             # due: a date like 2025-06-30, or empty\n\n{}",
+            config.categories().iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", "),
+            value_names::<Status>(),
+            value_names::<Priority>(),
+            toml::to_string(&original)?
+        );
+
+        let file = TempFile::create(id, &buffer)?;
+        let result = loop {
+            run_editor(&file.path)?;
+            buffer = fs::read_to_string(&file.path)?;
+
+            let mut edited = self.get(id)?.clone();
+            let error = match toml::from_str::<Document>(&buffer) {
+                Ok(document) if document == original => break Ok(None),
+                Ok(document) => match document.apply(&mut edited, config) {
+                    Ok(()) => break Ok(Some(edited)),
+                    Err(err) => err,
+                },
+                Err(err) => Error::InvalidEdit(err.message().to_string()),
+            };
+            eprintln!("{error}");
+            if !prompt("Edit again? [y/n] ")?.eq_ignore_ascii_case("y") {
+                break Err(Error::EditCancelled);
+            }
+        };
+        drop(file);
+
+        let Some(edited) = result? else {
+            println!("No changes made.");
+            return Ok(());
+        };
+        *self.get_mut(id)? = edited;
+        self.save(config)?;
+        report(config.output, &[self.get(id)?], None)
+    }
+}
+
+/// The buffer handed to the editor, deleted again when dropped so no error leaves the
+/// notes lying around.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Creates a new file only the current user can read. An existing file or link of the
+    /// same name is never opened, the next name is tried instead.
+    fn create(id: usize, contents: &str) -> Result<TempFile> {
+        let mut attempt = 0;
+        loop {
+            let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            let name = format!("arx-{id}-{}-{}.toml", std::process::id(), nanos.subsec_nanos());
+            let path = env::temp_dir().join(name);
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(mut file) => {
+                    let temp_file = TempFile { path };
+                    file.write_all(contents.as_bytes())?;
+                    return Ok(temp_file);
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists && attempt < 10 => attempt += 1,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Runs the user's editor on `path`, the editor may come with its own arguments
+/// like `code --wait`.
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if !status.success() {
+        return Err(Error::EditorFailed(editor));
+    }
+    Ok(())
+}
+
+fn value_of<T: ValueEnum>(field: &str, value: &str) -> Result<T> {
+    T::from_str(value.trim(), true).map_err(|_| {
+        let expected = value_names::<T>();
+        Error::InvalidEdit(format!("unknown {field} '{value}', expected one of {expected}"))
+    })
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
@@ -1,10 +1,29 @@
 pub type Result<T> = core::result::Result<T, Error>;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Bookmark with ID {0} not found")]
     IDNotFound(usize),
 
+    #[error("No bookmark matches '{0}'")]
+    NoMatch(String),
+
+    #[error("'{0}' matches {1} bookmarks, use an ID or a more specific query")]
+    AmbiguousQuery(String, usize),
+
+    #[error("Select bookmarks by ID, range, query or with --tag, --category or --status")]
+    EmptySelection,
+
+    #[error("No bookmarks selected")]
+    NothingSelected,
+
+    #[error("--interactive edits one bookmark at a time, {0} are selected")]
+    InteractiveEditOfMany(usize),
+
+    #[error("No bookmark picked")]
+    NothingPicked,
+
     #[error("Bookmark with ID {0} has no URL")]
     NoUrl(usize),
 
@@ -14,18 +33,60 @@ pub enum Error {
     #[error("Page {0} not found")]
     PageNotFound(usize),
 
-    #[error("Edit command requires at least one argument")]
+    #[error("Edit command requires at least one argument or --interactive")]
     NoEditSpecified,
 
+    #[error("Editor `{0}` exited with an error")]
+    EditorFailed(String),
+
+    #[error("Invalid bookmark: {0}")]
+    InvalidEdit(String),
+
+    #[error("Edit cancelled, no changes made")]
+    EditCancelled,
+
     #[error("Clipboard not found: {0}")]
     ClipboardNotFound(String),
 
     #[error("Could not copy to clipboard: {0}")]
     ClipboardCopyError(String),
 
+    #[error("No profile named `{0}`, see `arx profile list`")]
+    UnknownProfile(String),
+
+    #[error("`{0}` can't be used as a profile name, use letters, digits, '-' and '_'")]
+    InvalidProfileName(String),
+
+    #[error("Profile `{0}` already exists")]
+    ProfileExists(String),
+
+    #[error("Profile `{0}` is in use, switch to another one first")]
+    ProfileInUse(String),
+
+    #[error("The save location is overridden by --data-dir or ARX_DATA")]
+    SaveLocationOverridden,
+
+    #[error("Bookmarks already exist at {0}, nothing was moved")]
+    RelocationAborted(String),
+
+    #[error("The bookmarks written to {0} don't match yours, the original was kept")]
+    CopyMismatch(String),
+
     #[error("Invalid category: {0}")]
     CategoryParseError(String),
 
+    #[error("Unknown category '{0}', expected one of {1}")]
+    UnknownCategory(String, String),
+
+    #[error("Invalid color '{0}' for category '{1}'")]
+    InvalidColor(String, String),
+
+    #[error(
+        "Your bookmarks were saved by a newer arx (schema version {0}, this one knows up to {1}), \
+         please update arx"
+    )]
+    NewerSchema(u64, u64),
+
     #[error("JSON parse error: {0}")]
     ParseError(#[from] serde_json::Error),
 
@@ -38,6 +99,30 @@ pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[error("Unknown status '{0}' in the bookmarks database")]
+    UnknownStatus(String),
+
+    #[error("Unknown priority '{0}' in the bookmarks database")]
+    UnknownPriority(String),
+
+    #[error("No bookmarks found in {0}")]
+    NothingToImport(String),
+
+    #[error("Bookmarks at {0} are in use by another arx process, try again in a moment")]
+    StoreLocked(String),
+
+    #[error("Snapshot {0} not found, see `arx restore --list`")]
+    SnapshotNotFound(String),
+
+    #[error("Nothing to undo")]
+    NothingToUndo,
+
+    #[error("Nothing to redo")]
+    NothingToRedo,
+
     #[error("No config args provided")]
     NoConfigArgs,
 
@@ -0,0 +1,141 @@
+use std::fs;
+
+use crate::{
+    category::Category,
+    command_types::{ExportArgs, ExportFormat, FilterArgs, Status},
+    data::{Bookmark, BookmarkStore},
+    errors::Result,
+    netscape::{self, escape},
+};
+
+impl BookmarkStore {
+    pub fn export(&self, args: ExportArgs) -> Result<()> {
+        // an export is of everything the filters ask for, done and hidden bookmarks too
+        let bookmarks = self.filtered(&FilterArgs { all: true, ..args.filter }, None)?;
+        let data = match args.format {
+            ExportFormat::Md => markdown(&bookmarks),
+            ExportFormat::Html => html(&bookmarks),
+            ExportFormat::Csv => csv(&bookmarks),
+            ExportFormat::Netscape => netscape::write(
+                &bookmarks
+                    .iter()
+                    .filter_map(|b| {
+                        Some(netscape::Entry {
+                            title: b.title.clone(),
+                            url: b.url.clone()?,
+                            folders: vec![b.category.to_string()],
+                            tags: b.tags.clone().unwrap_or_default(),
+                            description: b.notes.clone(),
+                            added: Some(b.created_at),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            ExportFormat::Json => serde_json::to_string_pretty(&bookmarks)? + "\n",
+        };
+
+        match args.file {
+            Some(path) => {
+                fs::write(&path, data)?;
+                println!("Exported {} bookmark(s) to {}", bookmarks.len(), path.display());
+            }
+            None => print!("{data}"),
+        }
+        Ok(())
+    }
+}
+
+/// Groups bookmarks by category, in order of first appearance.
+fn by_category<'a>(bookmarks: &[&'a Bookmark]) -> Vec<(&'a Category, Vec<&'a Bookmark>)> {
+    let mut groups: Vec<(&Category, Vec<&Bookmark>)> = Vec::new();
+    for bookmark in bookmarks {
+        match groups.iter_mut().find(|(c, _)| *c == &bookmark.category) {
+            Some((_, group)) => group.push(bookmark),
+            None => groups.push((&bookmark.category, vec![bookmark])),
+        }
+    }
+    groups
+}
+
+fn markdown(bookmarks: &[&Bookmark]) -> String {
+    let mut out = String::from("# Bookmarks\n");
+    for (category, group) in by_category(bookmarks) {
+        out.push_str(&format!("\n## {category}\n\n"));
+        for bookmark in group {
+            let check = if bookmark.status == Status::Done { "x" } else { " " };
+            let title = bookmark.title.replace('[', "\\[").replace(']', "\\]");
+            match &bookmark.url {
+                Some(url) => out.push_str(&format!("- [{check}] [{title}](<{url}>)")),
+                None => out.push_str(&format!("- [{check}] {title}")),
+            }
+            for tag in bookmark.tags.iter().flatten() {
+                out.push_str(&format!(" `{tag}`"));
+            }
+            out.push('\n');
+            for line in bookmark.notes.iter().flat_map(|n| n.lines()) {
+                out.push_str(&format!("  > {line}\n"));
+            }
+        }
+    }
+    out
+}
+
+fn html(bookmarks: &[&Bookmark]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Bookmarks</title>\n</head>\n<body>\n<h1>Bookmarks</h1>\n",
+    );
+    for (category, group) in by_category(bookmarks) {
+        out.push_str(&format!("<h2>{}</h2>\n<ul>\n", escape(&category.to_string())));
+        for bookmark in group {
+            let title = escape(&bookmark.title);
+            out.push_str("<li>");
+            match &bookmark.url {
+                Some(url) => out.push_str(&format!("<a href=\"{}\">{title}</a>", escape(url))),
+                None => out.push_str(&title),
+            }
+            if bookmark.status != Status::None {
+                out.push_str(&format!(" <em>{}</em>", bookmark.status.name()));
+            }
+            if let Some(tags) = &bookmark.tags {
+                out.push_str(&format!(" <small>{}</small>", escape(&tags.join(", "))));
+            }
+            if let Some(notes) = &bookmark.notes {
+                out.push_str(&format!("<p>{}</p>", escape(notes).replace('\n', "<br>")));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn csv(bookmarks: &[&Bookmark]) -> String {
+    let mut out = String::from("id,title,category,url,tags,notes,status,hidden,created_at\n");
+    for bookmark in bookmarks {
+        let fields = [
+            bookmark.id.to_string(),
+            bookmark.title.clone(),
+            bookmark.category.to_string(),
+            bookmark.url.clone().unwrap_or_default(),
+            bookmark.tags.as_ref().map(|t| t.join(" ")).unwrap_or_default(),
+            bookmark.notes.clone().unwrap_or_default(),
+            bookmark.status.name().to_string(),
+            bookmark.hidden.to_string(),
+            bookmark.created_at.to_rfc3339(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,487 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use rusqlite::types::Value as SqlValue;
+
+use crate::{
+    category::Category,
+    command_types::Status,
+    config::Config,
+    data::Bookmark,
+    errors,
+    utils::value_names,
+};
+
+const FIELDS: &str =
+    "tag, category, status, title, url, notes, created, started, completed, id, hidden, has";
+
+/// A parsed `--where` expression such as
+/// `tag:rust and (category:book or category:course) and not status:done`.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Tag(Pattern),
+    Category(Category),
+    Status(Status),
+    Title(Pattern),
+    Url(Pattern),
+    Notes(Pattern),
+    /// a word without a field, looked for in the title, url, notes and tags
+    Text(Pattern),
+    Date(Date, Ordering, bool, NaiveDate),
+    Id(Ordering, bool, usize),
+    Hidden(bool),
+    Has(Has),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Date {
+    Created,
+    /// first marked as reading
+    Started,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Has {
+    Url,
+    Notes,
+    Tags,
+}
+
+/// Case-insensitive text match. Patterns with `*` or `?` have to match the whole value,
+/// plain ones match anywhere in it unless `whole` is set.
+#[derive(Debug, Clone)]
+struct Pattern {
+    text: String,
+    whole: bool,
+}
+
+impl Filter {
+    pub fn matches(&self, bookmark: &Bookmark) -> bool {
+        self.expr.matches(bookmark)
+    }
+
+    /// Whether the expression asks about the status or when it changed, in which case done
+    /// bookmarks aren't left out by default.
+    pub fn mentions_status(&self) -> bool {
+        self.expr.any(&|term| {
+            matches!(term, Term::Status(_) | Term::Date(Date::Started | Date::Completed, ..))
+        })
+    }
+
+    /// Whether the expression asks about hidden bookmarks.
+    pub fn mentions_hidden(&self) -> bool {
+        self.expr.any(&|term| matches!(term, Term::Hidden(_)))
+    }
+
+    /// An SQL condition on the columns of an SQLite store that holds for at least every
+    /// matching bookmark, so only those rows are read before `matches` checks them.
+    /// `None` when no part of the expression can be told from the columns alone.
+    pub fn sql(&self) -> Option<(String, Vec<SqlValue>)> {
+        self.expr.sql(false)
+    }
+
+    /// Checks `category:` terms against the configured categories like `--category` does,
+    /// turning aliases and other casings into the category's name.
+    pub fn resolve_categories(&mut self, config: &Config) -> errors::Result<()> {
+        self.expr.terms_mut(&mut |term| {
+            if let Term::Category(category) = term {
+                *category = config.category(category.name())?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Expr {
+    fn matches(&self, b: &Bookmark) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(b) && right.matches(b),
+            Expr::Or(left, right) => left.matches(b) || right.matches(b),
+            Expr::Not(expr) => !expr.matches(b),
+            Expr::Term(term) => term.matches(b),
+        }
+    }
+
+    fn any(&self, f: &dyn Fn(&Term) -> bool) -> bool {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => left.any(f) || right.any(f),
+            Expr::Not(expr) => expr.any(f),
+            Expr::Term(term) => f(term),
+        }
+    }
+
+    /// The condition for exactly the matching rows with `exact`, otherwise one that may let
+    /// more rows through, e.g. just the left side of an `and` whose right side has no SQL.
+    fn sql(&self, exact: bool) -> Option<(String, Vec<SqlValue>)> {
+        match self {
+            Expr::And(left, right) => match (left.sql(exact), right.sql(exact)) {
+                (Some((left, mut params)), Some((right, right_params))) => {
+                    params.extend(right_params);
+                    Some((format!("({left} AND {right})"), params))
+                }
+                (Some(side), None) | (None, Some(side)) if !exact => Some(side),
+                _ => None,
+            },
+            Expr::Or(left, right) => {
+                let (left, mut params) = left.sql(exact)?;
+                let (right, right_params) = right.sql(exact)?;
+                params.extend(right_params);
+                Some((format!("({left} OR {right})"), params))
+            }
+            Expr::Not(expr) => expr.sql(true).map(|(sql, params)| (format!("NOT {sql}"), params)),
+            Expr::Term(term) => term.sql(),
+        }
+    }
+
+    fn terms_mut(
+        &mut self,
+        f: &mut dyn FnMut(&mut Term) -> errors::Result<()>,
+    ) -> errors::Result<()> {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.terms_mut(f)?;
+                right.terms_mut(f)
+            }
+            Expr::Not(expr) => expr.terms_mut(f),
+            Expr::Term(term) => f(term),
+        }
+    }
+}
+
+impl Term {
+    fn matches(&self, b: &Bookmark) -> bool {
+        let tags = b.tags.as_deref().unwrap_or_default();
+        match self {
+            Term::Tag(p) => tags.iter().any(|tag| p.matches(tag)),
+            Term::Category(category) => &b.category == category,
+            Term::Status(status) => &b.status == status,
+            Term::Title(p) => p.matches(&b.title),
+            Term::Url(p) => b.url.as_ref().is_some_and(|url| p.matches(url)),
+            Term::Notes(p) => b.notes.as_ref().is_some_and(|notes| p.matches(notes)),
+            Term::Text(p) => {
+                p.matches(&b.title)
+                    || b.url.as_ref().is_some_and(|url| p.matches(url))
+                    || b.notes.as_ref().is_some_and(|notes| p.matches(notes))
+                    || tags.iter().any(|tag| p.matches(tag))
+            }
+            Term::Date(field, ordering, or_equal, date) => {
+                let at = match field {
+                    Date::Created => Some(b.created_at),
+                    Date::Started => b.started_at(),
+                    Date::Completed => b.completed_at,
+                };
+                at.is_some_and(|at| {
+                    let day = at.with_timezone(&Local).date_naive();
+                    compare(day.cmp(date), *ordering, *or_equal)
+                })
+            }
+            Term::Id(ordering, or_equal, id) => compare(b.id.cmp(id), *ordering, *or_equal),
+            Term::Hidden(hidden) => b.hidden == *hidden,
+            Term::Has(Has::Url) => b.url.is_some(),
+            Term::Has(Has::Notes) => b.notes.is_some(),
+            Term::Has(Has::Tags) => !tags.is_empty(),
+        }
+    }
+}
+
+impl Term {
+    /// The exact SQL condition for the term, text patterns and dates have none since
+    /// SQLite folds case and time zones differently.
+    fn sql(&self) -> Option<(String, Vec<SqlValue>)> {
+        let (sql, params) = match self {
+            Term::Category(category) => ("category = ?", vec![category.to_string().into()]),
+            Term::Status(status) => ("status = ?", vec![status.name().to_string().into()]),
+            Term::Id(ordering, or_equal, id) => {
+                let operator = match (ordering, or_equal) {
+                    (Ordering::Greater, true) => ">=",
+                    (Ordering::Greater, false) => ">",
+                    (Ordering::Less, true) => "<=",
+                    (Ordering::Less, false) => "<",
+                    (Ordering::Equal, _) => "=",
+                };
+                return Some((format!("id {operator} ?"), vec![(*id as i64).into()]));
+            }
+            Term::Hidden(hidden) => ("hidden = ?", vec![(*hidden).into()]),
+            Term::Has(Has::Url) => ("url IS NOT NULL", Vec::new()),
+            Term::Has(Has::Notes) => ("notes IS NOT NULL", Vec::new()),
+            _ => return None,
+        };
+        Some((sql.to_string(), params))
+    }
+}
+
+fn compare(actual: Ordering, wanted: Ordering, or_equal: bool) -> bool {
+    actual == wanted || (or_equal && actual == Ordering::Equal)
+}
+
+impl Pattern {
+    fn new(text: &str, whole: bool) -> Pattern {
+        Pattern { text: text.to_lowercase(), whole }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let value = value.to_lowercase();
+        if self.text.contains(['*', '?']) {
+            glob(&self.text, &value)
+        } else if self.whole {
+            value == self.text
+        } else {
+            value.contains(&self.text)
+        }
+    }
+}
+
+/// Matches `*` (any run of characters) and `?` (one character) against the whole `value`.
+fn glob(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    // where the last `*` was and how much of the value it has swallowed so far
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match star {
+                Some((star_p, star_v)) => {
+                    p = star_p + 1;
+                    v = star_v + 1;
+                    star = Some((star_p, star_v + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word { text: String, quoted: bool },
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word { text, quoted: false } if text.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut text = String::new();
+                let mut quoted = false;
+                let mut in_quotes = false;
+                while let Some(&c) = chars.peek() {
+                    if !in_quotes && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        in_quotes = !in_quotes;
+                        quoted = true;
+                    } else {
+                        text.push(c);
+                    }
+                }
+                if in_quotes {
+                    return Err("missing closing quote".to_string());
+                }
+                tokens.push(Token::Word { text, quoted });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, `and` binds tighter than `or` and may be left out
+/// between terms.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.position += 1;
+        self.tokens.get(self.position - 1)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek().is_some_and(|t| t.is_keyword("or")) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        loop {
+            match self.peek() {
+                Some(t) if t.is_keyword("and") => {
+                    self.next();
+                }
+                Some(t) if t.is_keyword("or") || *t == Token::Close => break,
+                Some(_) => {}
+                None => break,
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek().is_some_and(|t| t.is_keyword("not")) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing closing ')'".to_string()),
+                }
+            }
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(Token::Word { text, quoted }) if *quoted || !is_operator(text) => {
+                let text = text.clone();
+                Ok(Expr::Term(term(&text)?))
+            }
+            Some(Token::Word { text, .. }) => Err(format!("expected a term, found '{text}'")),
+            None => Err("expression ends too early".to_string()),
+        }
+    }
+}
+
+fn is_operator(word: &str) -> bool {
+    ["and", "or", "not"].iter().any(|op| word.eq_ignore_ascii_case(op))
+}
+
+fn term(word: &str) -> Result<Term, String> {
+    let Some((field, value)) = word.split_once(':') else {
+        return Ok(Term::Text(Pattern::new(word, false)));
+    };
+    if value.is_empty() {
+        return Err(format!("'{field}:' needs a value"));
+    }
+    Ok(match field.to_lowercase().as_str() {
+        "tag" | "tags" => Term::Tag(Pattern::new(value, true)),
+        "category" => {
+            Term::Category(value.parse().map_err(|_| format!("invalid category '{value}'"))?)
+        }
+        "status" => Term::Status(value_of("status", value)?),
+        "title" => Term::Title(Pattern::new(value, false)),
+        "url" => Term::Url(Pattern::new(value, false)),
+        "notes" => Term::Notes(Pattern::new(value, false)),
+        "created" | "started" | "completed" => {
+            let field = match field.to_lowercase().as_str() {
+                "created" => Date::Created,
+                "started" => Date::Started,
+                _ => Date::Completed,
+            };
+            let (ordering, or_equal, date) = comparison(value);
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("'{date}' is not a date like 2025-01-31"))?;
+            Term::Date(field, ordering, or_equal, date)
+        }
+        "id" => {
+            let (ordering, or_equal, id) = comparison(value);
+            let id = id.parse().map_err(|_| format!("'{id}' is not an ID"))?;
+            Term::Id(ordering, or_equal, id)
+        }
+        "hidden" => Term::Hidden(
+            value.parse().map_err(|_| format!("hidden is true or false, not '{value}'"))?,
+        ),
+        "has" => Term::Has(match value.to_lowercase().as_str() {
+            "url" => Has::Url,
+            "notes" => Has::Notes,
+            "tags" => Has::Tags,
+            _ => return Err(format!("has: takes url, notes or tags, not '{value}'")),
+        }),
+        _ => return Err(format!("unknown field '{field}', expected one of {FIELDS}")),
+    })
+}
+
+/// Splits `>=2025-01-01` into the wanted ordering, whether equal is fine too and the rest.
+fn comparison(value: &str) -> (Ordering, bool, &str) {
+    for (prefix, ordering, or_equal) in [
+        (">=", Ordering::Greater, true),
+        ("<=", Ordering::Less, true),
+        (">", Ordering::Greater, false),
+        ("<", Ordering::Less, false),
+        ("=", Ordering::Equal, false),
+    ] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (ordering, or_equal, rest);
+        }
+    }
+    (Ordering::Equal, false, value)
+}
+
+fn value_of<T: ValueEnum>(field: &str, value: &str) -> Result<T, String> {
+    T::from_str(value, true).map_err(|_| {
+        format!("unknown {field} '{value}', expected one of {}", value_names::<T>())
+    })
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?, position: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Filter { expr }),
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(_) => Err("unexpected text after the expression".to_string()),
+        }
+    }
+}
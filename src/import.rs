@@ -0,0 +1,78 @@
+use std::{collections::HashSet, fs};
+
+use crate::{
+    command_types::{ImportArgs, ImportFormat},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    netscape,
+    output::report,
+};
+
+impl BookmarkStore {
+    pub fn import(&mut self, args: ImportArgs, config: &Config) -> Result<()> {
+        let data = fs::read_to_string(&args.file)?;
+        let entries = match args.format {
+            ImportFormat::Netscape => netscape::parse(&data),
+        };
+        if entries.is_empty() {
+            return Err(Error::NothingToImport(args.file.display().to_string()));
+        }
+
+        let mut known_urls: HashSet<String> =
+            self.bookmarks.iter().filter_map(|b| b.url.clone()).collect();
+        let first_new_id = self.next_id;
+        let mut duplicates = 0;
+        for entry in entries {
+            if !known_urls.insert(entry.url.clone()) {
+                duplicates += 1;
+                continue;
+            }
+            let mut tags: Vec<String> = entry.folders.iter().map(|f| tag_from(f)).collect();
+            for tag in entry.tags.iter().map(|t| tag_from(t)) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            tags.retain(|t| !t.is_empty());
+
+            self.bookmarks.push(Bookmark {
+                id: self.next_id,
+                title: entry.title,
+                category: args.category.clone().unwrap_or_default(),
+                url: Some(entry.url),
+                tags: if tags.is_empty() { None } else { Some(tags) },
+                notes: entry.description,
+                status: Default::default(),
+                hidden: false,
+                created_at: entry.added.unwrap_or_else(chrono::Utc::now),
+                completed_at: None,
+                history: Vec::new(),
+                priority: None,
+                due: None,
+            });
+            self.next_id += 1;
+        }
+
+        self.save(config)?;
+        let imported: Vec<&Bookmark> =
+            self.bookmarks.iter().filter(|b| b.id >= first_new_id).collect();
+        report(
+            config.output,
+            &imported,
+            Some(&format!(
+                "Imported {} bookmark(s), skipped {duplicates} already saved url(s)",
+                imported.len()
+            )),
+        )
+    }
+}
+
+/// Tags are split on spaces when added by hand, so folder names like "Bookmarks bar"
+/// become "bookmarks-bar".
+fn tag_from(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
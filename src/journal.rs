@@ -0,0 +1,279 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command_types::{ExistingStore, UndoArgs},
+    config::Config,
+    data::{Arx, Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    output::report,
+    utils::{prompt, write_atomic},
+};
+
+/// Most operations kept in the journal.
+const MAX_ENTRIES: usize = 100;
+
+/// Operations that can be undone and redone, most recent last.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Journal {
+    pub undo: Vec<Entry>,
+    pub redo: Vec<Entry>,
+}
+
+/// One command's changes to bookmarks and the config.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Entry {
+    pub command: String,
+    pub at: DateTime<Utc>,
+    pub changes: Vec<Change>,
+    pub config: Option<ConfigChange>,
+}
+
+/// A bookmark before and after a command, `None` when it didn't exist.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Change {
+    pub before: Option<Bookmark>,
+    pub after: Option<Bookmark>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigChange {
+    pub before: Config,
+    pub after: Config,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+impl Journal {
+    /// The journal lives next to the bookmarks it belongs to.
+    pub fn path(store_path: &Path) -> PathBuf {
+        store_path.with_file_name("journal.json")
+    }
+
+    pub fn load(store_path: &Path) -> Result<Journal> {
+        let path = Journal::path(store_path);
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn save(&self, store_path: &Path) -> Result<()> {
+        let data = serde_json::to_string(self)?;
+        write_atomic(&Journal::path(store_path), data.as_bytes())
+    }
+}
+
+impl Arx {
+    /// Runs a command that changes bookmarks or the config and records what it changed,
+    /// so `arx undo` can revert it later.
+    pub fn journaled(
+        &mut self,
+        command: &str,
+        run: impl FnOnce(&mut Arx) -> Result<()>,
+    ) -> Result<()> {
+        let bookmarks_before = self.store.bookmarks.clone();
+        let config_before = self.config.clone();
+        run(self)?;
+
+        let changes = diff(&bookmarks_before, &self.store.bookmarks);
+        let config = (config_before != self.config).then(|| ConfigChange {
+            before: config_before,
+            after: self.config.clone(),
+        });
+        if changes.is_empty() && config.is_none() {
+            return Ok(());
+        }
+
+        let mut journal = Journal::load(&self.store.path)?;
+        journal.undo.push(Entry {
+            command: command.to_string(),
+            at: Utc::now(),
+            changes,
+            config,
+        });
+        if journal.undo.len() > MAX_ENTRIES {
+            journal.undo.remove(0);
+        }
+        journal.redo.clear();
+        journal.save(&self.store.path)
+    }
+
+    pub fn undo(&mut self, args: UndoArgs) -> Result<()> {
+        self.replay(args, Direction::Undo)
+    }
+
+    pub fn redo(&mut self, args: UndoArgs) -> Result<()> {
+        self.replay(args, Direction::Redo)
+    }
+
+    fn replay(&mut self, args: UndoArgs, direction: Direction) -> Result<()> {
+        let mut journal = Journal::load(&self.store.path)?;
+        let (from, to) = match direction {
+            Direction::Undo => (&mut journal.undo, &mut journal.redo),
+            Direction::Redo => (&mut journal.redo, &mut journal.undo),
+        };
+        let Some(entry) = from.last().cloned() else {
+            return Err(match direction {
+                Direction::Undo => Error::NothingToUndo,
+                Direction::Redo => Error::NothingToRedo,
+            });
+        };
+
+        let verb = match direction {
+            Direction::Undo => "Undo",
+            Direction::Redo => "Redo",
+        };
+        eprintln!(
+            "{verb} `{}` from {}:",
+            entry.command,
+            entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S")
+        );
+        for line in entry.describe(direction) {
+            eprintln!("  {line}");
+        }
+        if !args.yes && !prompt("Proceed? [y/n] ")?.eq_ignore_ascii_case("y") {
+            return Ok(());
+        }
+
+        let mut touched = Vec::new();
+        for change in entry.changes.iter().rev() {
+            let (current, target) = match direction {
+                Direction::Undo => (&change.after, &change.before),
+                Direction::Redo => (&change.before, &change.after),
+            };
+            let id = current.as_ref().or(target.as_ref()).map(|b| b.id).expect("empty change");
+            self.store.put(id, target.clone());
+            touched.push(id);
+        }
+        if let Some(change) = &entry.config {
+            let target = match direction {
+                Direction::Undo => change.before.clone(),
+                Direction::Redo => change.after.clone(),
+            };
+            self.restore_config(target)?;
+        }
+        self.store.save(&self.config)?;
+
+        to.push(from.pop().expect("entry exists"));
+        // the store may have moved back, taking the journal with it
+        journal.save(&self.store.path)?;
+
+        let bookmarks: Vec<&Bookmark> =
+            touched.iter().filter_map(|id| self.store.get(*id).ok()).collect();
+        report(
+            self.config.output,
+            &bookmarks,
+            Some(&format!("{verb} of `{}` done", entry.command)),
+        )
+    }
+
+    /// Puts a previous config back, moving the store back if its location changed.
+    fn restore_config(&mut self, mut target: Config) -> Result<()> {
+        // these come from the command line, not the recorded config
+        target.output = self.config.output;
+        target.config_path = self.config.config_path.clone();
+        target.data_dir = self.config.data_dir.clone();
+        target.active_profile = self.config.active_profile.clone();
+        if target.store_path() != self.store.path {
+            let existing = Some(ExistingStore::Abort);
+            self.store.relocate(target.store_path(), &self.config, existing)?;
+        }
+        self.config = target;
+        self.store.save_config(&mut self.config)
+    }
+}
+
+impl BookmarkStore {
+    /// Replaces, inserts or (with `None`) removes the bookmark with ID `id`, keeping the
+    /// bookmarks ordered by ID.
+    fn put(&mut self, id: usize, bookmark: Option<Bookmark>) {
+        match (self.position(id), bookmark) {
+            (Ok(index), Some(bookmark)) => self.bookmarks[index] = bookmark,
+            (Ok(index), None) => {
+                self.bookmarks.remove(index);
+            }
+            (Err(_), Some(bookmark)) => {
+                let index = self.bookmarks.partition_point(|b| b.id < id);
+                self.bookmarks.insert(index, bookmark);
+            }
+            (Err(_), None) => {}
+        }
+    }
+}
+
+impl Entry {
+    /// Human readable lines for what undoing or redoing this entry does.
+    fn describe(&self, direction: Direction) -> Vec<String> {
+        let mut lines = Vec::new();
+        for change in &self.changes {
+            let (from, to) = match direction {
+                Direction::Undo => (&change.after, &change.before),
+                Direction::Redo => (&change.before, &change.after),
+            };
+            match (from, to) {
+                (Some(b), None) => lines.push(format!("remove #{} '{}'", b.id, b.title)),
+                (None, Some(b)) => lines.push(format!("bring back #{} '{}'", b.id, b.title)),
+                (Some(from), Some(to)) => {
+                    let fields = field_changes(from, to);
+                    lines.push(format!("#{} '{}': {}", to.id, from.title, fields.join(", ")));
+                }
+                (None, None) => {}
+            }
+        }
+        if self.config.is_some() {
+            lines.push("restore the previous config".to_string());
+        }
+        lines
+    }
+}
+
+fn field_changes(from: &Bookmark, to: &Bookmark) -> Vec<String> {
+    let show = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let fields = [
+        ("title", Some(from.title.clone()), Some(to.title.clone())),
+        ("url", from.url.clone(), to.url.clone()),
+        ("category", Some(from.category.to_string()), Some(to.category.to_string())),
+        ("tags", from.tags.as_ref().map(|t| t.join(" ")), to.tags.as_ref().map(|t| t.join(" "))),
+        ("notes", from.notes.clone(), to.notes.clone()),
+        ("status", Some(from.status.name().to_string()), Some(to.status.name().to_string())),
+        ("hidden", Some(from.hidden.to_string()), Some(to.hidden.to_string())),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, from, to)| from != to)
+        .map(|(name, from, to)| format!("{name} {} → {}", show(from), show(to)))
+        .collect()
+}
+
+/// Changed bookmarks between two versions of the store, matched by ID.
+pub fn diff(before: &[Bookmark], after: &[Bookmark]) -> Vec<Change> {
+    let before_by_id: HashMap<usize, &Bookmark> = before.iter().map(|b| (b.id, b)).collect();
+    let after_by_id: HashMap<usize, &Bookmark> = after.iter().map(|b| (b.id, b)).collect();
+    let mut changes: Vec<Change> = before
+        .iter()
+        .filter(|b| after_by_id.get(&b.id).copied() != Some(*b))
+        .map(|b| Change {
+            before: Some(b.clone()),
+            after: after_by_id.get(&b.id).map(|a| (*a).clone()),
+        })
+        .collect();
+    changes.extend(
+        after
+            .iter()
+            .filter(|a| !before_by_id.contains_key(&a.id))
+            .map(|a| Change { before: None, after: Some(a.clone()) }),
+    );
+    changes
+}
@@ -1,15 +1,33 @@
 use crate::errors::Result;
-use clap::Parser;
+use clap::FromArgMatches;
 use comfy_table::Cell;
 use command_types::{CLI, ListFields, Status, Subcommands};
+use config::Overrides;
 use data::{Arx, BookmarkStore};
 use errors::Error;
 
+mod backup;
+mod category;
 mod command_types;
 mod commands;
 mod config;
 mod data;
+mod editor;
 mod errors;
+mod export;
+mod filter;
+mod import;
+mod journal;
+mod netscape;
+mod output;
+mod profile;
+mod relocate;
+mod resolve;
+mod schema;
+mod search;
+mod select;
+mod storage;
+mod table;
 mod utils;
 
 fn main() {
@@ -20,17 +38,50 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let cli = CLI::parse();
-    let mut arx = Arx::init()?;
+    // categories come from the config, so it's loaded before the arguments are parsed
+    let mut config = config::load_config(&Overrides::from_args(std::env::args_os()))?;
+    let matches = category::with_categories(CLI::command(), &config).get_matches();
+    let mut cli = CLI::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if let Some(filter) = cli.command.filter_mut() {
+        filter.resolve_categories(&config)?;
+    }
+    let profile = config.use_profile(cli.profile.clone());
+    // `arx profile` has to work even when the config names a profile that is gone
+    if !matches!(cli.command, Subcommands::Profile(_)) {
+        profile?;
+    }
+    let mut arx = Arx::init(config, cli.command.writes(), cli.command.scope())?;
+    arx.config.output = cli.output_format();
+    if !matches!(cli.command, Subcommands::MigrateStorage(_) | Subcommands::Profile(_)) {
+        arx.store.check_storage(&arx.config);
+    }
     match cli.command {
-        Subcommands::Add(args) => arx.store.add(args)?,
+        Subcommands::Add(args) => arx.journaled("add", |arx| arx.store.add(args, &arx.config))?,
         Subcommands::List(args) => arx.store.list(args, &arx.config)?,
-        Subcommands::Remove(query) => arx.store.remove(query)?,
-        Subcommands::Edit(query) => arx.store.edit(query)?,
-        Subcommands::Done(query) => arx.store.done(query)?,
-        Subcommands::Open(query) => arx.store.open(query)?,
-        Subcommands::CopyUrl(query) => arx.store.copy_url(query)?,
-        Subcommands::Config(args) => arx.store.config(args, &mut arx.config)?,
+        Subcommands::Search(args) => arx.store.search(args, &arx.config)?,
+        Subcommands::Remove(args) => {
+            arx.journaled("remove", |arx| arx.store.remove(args, &arx.config))?
+        }
+        Subcommands::Edit(args) => arx.journaled("edit", |arx| arx.store.edit(args, &arx.config))?,
+        Subcommands::Done(args) => arx.journaled("done", |arx| arx.store.done(args, &arx.config))?,
+        Subcommands::Open(args) => arx.store.open(args, &arx.config)?,
+        Subcommands::CopyUrl(query) => arx.store.copy_url(query, &arx.config)?,
+        Subcommands::Export(args) => arx.store.export(args)?,
+        Subcommands::Import(args) => {
+            arx.journaled("import", |arx| arx.store.import(args, &arx.config))?
+        }
+        Subcommands::Undo(args) => arx.undo(args)?,
+        Subcommands::Redo(args) => arx.redo(args)?,
+        Subcommands::Restore(args) => {
+            arx.journaled("restore", |arx| arx.store.restore(args, &arx.config))?
+        }
+        Subcommands::Config(args) => {
+            arx.journaled("config", |arx| arx.store.config(args, &mut arx.config))?
+        }
+        Subcommands::Profile(args) => arx.store.profile(args, &mut arx.config)?,
+        Subcommands::MigrateStorage(args) => arx.journaled("migrate-storage", |arx| {
+            arx.store.migrate_storage(args, &mut arx.config)
+        })?,
     }
     Ok(())
 }
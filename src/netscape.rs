@@ -0,0 +1,264 @@
+//! Reading and writing the Netscape bookmark file format browsers use for their html exports.
+
+use chrono::{DateTime, Utc};
+
+/// A bookmark in a Netscape export. `folders` is the path of folder names it is in.
+#[derive(Debug)]
+pub struct Entry {
+    pub title: String,
+    pub url: String,
+    pub folders: Vec<String>,
+    pub tags: Vec<String>,
+    pub description: Option<String>,
+    pub added: Option<DateTime<Utc>>,
+}
+
+enum Token<'a> {
+    Open(String, Vec<(String, String)>),
+    Close(String),
+    Text(&'a str),
+}
+
+/// Parses the bookmarks out of a Netscape bookmark file. The format is loose html, so
+/// unknown tags are skipped rather than rejected.
+pub fn parse(html: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    // one element per open <DL>, holding the folder name if it belongs to a folder
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder: Option<String> = None;
+    let mut anchor: Option<Vec<(String, String)>> = None;
+    let mut in_heading = false;
+    let mut in_description = false;
+    let mut text = String::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Text(t) => {
+                if anchor.is_some() || in_heading {
+                    text.push_str(t);
+                } else if in_description {
+                    let description = decode_entities(t.trim());
+                    if let Some(entry) = entries.last_mut().filter(|_| !description.is_empty()) {
+                        entry.description = Some(description);
+                    }
+                    in_description = false;
+                }
+            }
+            Token::Open(name, attrs) => match name.as_str() {
+                "a" => {
+                    anchor = Some(attrs);
+                    text.clear();
+                }
+                "h3" => {
+                    in_heading = true;
+                    text.clear();
+                }
+                "dl" => folders.push(pending_folder.take()),
+                "dd" => in_description = true,
+                "dt" => in_description = false,
+                _ => {}
+            },
+            Token::Close(name) => match name.as_str() {
+                "a" => {
+                    let Some(attrs) = anchor.take() else { continue };
+                    let Some(url) = attr(&attrs, "href").filter(|u| !u.is_empty()) else {
+                        continue;
+                    };
+                    let title = decode_entities(text.trim());
+                    entries.push(Entry {
+                        title: if title.is_empty() { url.clone() } else { title },
+                        url,
+                        folders: folders.iter().flatten().cloned().collect(),
+                        tags: attr(&attrs, "tags")
+                            .map(|tags| {
+                                tags.split(',')
+                                    .map(|t| t.trim().to_string())
+                                    .filter(|t| !t.is_empty())
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                        description: None,
+                        added: attr(&attrs, "add_date")
+                            .and_then(|date| date.parse::<i64>().ok())
+                            .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+                    });
+                }
+                "h3" => {
+                    in_heading = false;
+                    pending_folder = Some(decode_entities(text.trim()));
+                }
+                "dl" => {
+                    folders.pop();
+                }
+                _ => {}
+            },
+        }
+    }
+    entries
+}
+
+/// Writes `entries` as a Netscape bookmark file, with one folder per distinct first folder.
+pub fn write(entries: &[Entry]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n",
+    );
+    let mut folders: Vec<Option<&String>> = Vec::new();
+    for entry in entries {
+        if !folders.contains(&entry.folders.first()) {
+            folders.push(entry.folders.first());
+        }
+    }
+    for folder in folders {
+        let indent = match folder {
+            Some(name) => {
+                out.push_str(&format!("    <DT><H3>{}</H3>\n    <DL><p>\n", escape(name)));
+                "        "
+            }
+            None => "    ",
+        };
+        for entry in entries.iter().filter(|e| e.folders.first() == folder) {
+            out.push_str(&format!("{indent}<DT><A HREF=\"{}\"", escape(&entry.url)));
+            if let Some(added) = entry.added {
+                out.push_str(&format!(" ADD_DATE=\"{}\"", added.timestamp()));
+            }
+            if !entry.tags.is_empty() {
+                out.push_str(&format!(" TAGS=\"{}\"", escape(&entry.tags.join(","))));
+            }
+            out.push_str(&format!(">{}</A>\n", escape(&entry.title)));
+            if let Some(description) = &entry.description {
+                out.push_str(&format!("{indent}<DD>{}\n", escape(description)));
+            }
+        }
+        if folder.is_some() {
+            out.push_str("    </DL><p>\n");
+        }
+    }
+    out.push_str("</DL><p>\n");
+    out
+}
+
+/// Escapes text for use in html content and quoted attributes.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn attr(attrs: &[(String, String)], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| decode_entities(value))
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim().to_lowercase()));
+        } else if !tag.starts_with('!') {
+            let (name, attrs) = parse_tag(tag);
+            tokens.push(Token::Open(name, attrs));
+        }
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    tokens
+}
+
+/// Splits `A HREF="x" ADD_DATE=1` into its lowercase name and attributes.
+fn parse_tag(tag: &str) -> (String, Vec<(String, String)>) {
+    let tag = tag.trim().trim_end_matches('/');
+    let (name, mut rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+    let mut attrs = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_lowercase();
+        rest = rest[key_end..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = value[1..].find(quote).map_or(value.len(), |i| i + 1);
+                        rest = value.get(end + 1..).unwrap_or("");
+                        value[1..end].to_string()
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        rest = &value[end..];
+                        value[..end].to_string()
+                    }
+                }
+            }
+            None => String::new(),
+        };
+        attrs.push((key, value));
+    }
+    (name.to_lowercase(), attrs)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,52 @@
+use crate::{command_types::OutputFormat, data::Bookmark, errors::Result};
+
+const TSV_HEADER: &str = "id\ttitle\tcategory\turl\ttags\tnotes\tstatus\thidden\tcreated_at";
+
+/// Prints the bookmarks a command worked on. With table output only `message` is
+/// printed, the structured formats print the bookmark records instead.
+pub fn report(format: OutputFormat, bookmarks: &[&Bookmark], message: Option<&str>) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            if let Some(message) = message {
+                println!("{message}");
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string(bookmarks)?),
+        OutputFormat::Jsonl => {
+            for bookmark in bookmarks {
+                println!("{}", serde_json::to_string(bookmark)?);
+            }
+        }
+        OutputFormat::Tsv => {
+            println!("{TSV_HEADER}");
+            for bookmark in bookmarks {
+                println!("{}", tsv_row(bookmark));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tsv_row(bookmark: &Bookmark) -> String {
+    [
+        bookmark.id.to_string(),
+        bookmark.title.clone(),
+        bookmark.category.to_string(),
+        bookmark.url.clone().unwrap_or_default(),
+        bookmark.tags.as_ref().map(|t| t.join(" ")).unwrap_or_default(),
+        bookmark.notes.clone().unwrap_or_default(),
+        bookmark.status.name().to_string(),
+        bookmark.hidden.to_string(),
+        bookmark.created_at.to_rfc3339(),
+    ]
+    .iter()
+    .map(|field| {
+        field
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    })
+    .collect::<Vec<_>>()
+    .join("\t")
+}
@@ -0,0 +1,227 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command_types::{
+        PageBy, ProfileArgs, ProfileCommand, ProfileCreateArgs, StorageKind, TableStyle,
+    },
+    config::{Config, default_data_dir},
+    data::{BookmarkStore, StoreLock},
+    errors::{Error, Result},
+    journal::Journal,
+    storage,
+    table::{header, new_table},
+    utils::prompt,
+};
+
+/// Name of the main bookmarks, the ones used without a profile.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A profile as set up in `config.toml`, whatever it leaves out comes from the main config:
+///
+/// ```toml
+/// [profiles.work]
+/// table_style = "utf8_borders_only"
+/// page_by = 20
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProfileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_location: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_style: Option<TableStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_by: Option<PageBy>,
+}
+
+impl Config {
+    /// Uses the profile `name`, or the current one from the config when it isn't given.
+    pub fn use_profile(&mut self, name: Option<String>) -> Result<()> {
+        let name = name.or_else(|| self.profile.clone()).filter(|n| n != DEFAULT_PROFILE);
+        if let Some(name) = &name
+            && !self.profiles.contains_key(name)
+        {
+            return Err(Error::UnknownProfile(name.clone()));
+        }
+        self.active_profile = name;
+        Ok(())
+    }
+
+    /// Settings of the profile in use, `None` for the main bookmarks.
+    pub fn profile_config(&self) -> Option<&ProfileConfig> {
+        self.active_profile.as_ref().and_then(|name| self.profiles.get(name))
+    }
+
+    pub fn profile_config_mut(&mut self) -> Option<&mut ProfileConfig> {
+        self.active_profile.as_ref().and_then(|name| self.profiles.get_mut(name))
+    }
+
+    pub fn table_style(&self) -> Option<&TableStyle> {
+        self.profile_config().and_then(|p| p.table_style.as_ref()).or(self.table_style.as_ref())
+    }
+
+    pub fn page_by(&self) -> Option<PageBy> {
+        self.profile_config().and_then(|p| p.page_by).or(self.page_by)
+    }
+
+    /// The bookmarks file of profile `name`, the main one for `None`. Profiles without
+    /// a save location of their own live under `profiles/` in the data directory.
+    pub fn profile_path(&self, name: Option<&str>) -> PathBuf {
+        let Some(name) = name else {
+            return match &self.data_dir {
+                Some(dir) => self.store_in(dir),
+                None => self.save_location.clone(),
+            };
+        };
+        let own = self.profiles.get(name).and_then(|p| p.save_location.clone());
+        let data_dir = match (&self.data_dir, own) {
+            (None, Some(path)) => return path,
+            (Some(dir), _) => dir.clone(),
+            (None, None) => default_data_dir(),
+        };
+        self.store_in(&data_dir.join("profiles").join(name))
+    }
+
+    /// The bookmarks file in `dir`, of whichever storage is there already and otherwise
+    /// of the configured one.
+    fn store_in(&self, dir: &Path) -> PathBuf {
+        let kind = self.storage.unwrap_or_default();
+        let other = match kind {
+            StorageKind::Json => StorageKind::Sqlite,
+            StorageKind::Sqlite => StorageKind::Json,
+        };
+        [kind, other]
+            .into_iter()
+            .map(|kind| dir.join(kind.file_name()))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| dir.join(kind.file_name()))
+    }
+}
+
+impl BookmarkStore {
+    pub fn profile(&self, args: ProfileArgs, config: &mut Config) -> Result<()> {
+        match args.command {
+            ProfileCommand::List => self.list_profiles(config),
+            ProfileCommand::Create(args) => self.create_profile(args, config),
+            ProfileCommand::Switch { name } => {
+                if name != DEFAULT_PROFILE && !config.profiles.contains_key(&name) {
+                    return Err(Error::UnknownProfile(name));
+                }
+                config.profile = (name != DEFAULT_PROFILE).then(|| name.clone());
+                self.save_config(config)?;
+                println!("Switched to profile `{name}`");
+                Ok(())
+            }
+            ProfileCommand::Delete { name, yes } => self.delete_profile(&name, yes, config),
+        }
+    }
+
+    fn create_profile(&self, args: ProfileCreateArgs, config: &mut Config) -> Result<()> {
+        let name = args.name;
+        let valid = name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if name.is_empty() || !valid || name == DEFAULT_PROFILE {
+            return Err(Error::InvalidProfileName(name));
+        }
+        if config.profiles.contains_key(&name) {
+            return Err(Error::ProfileExists(name));
+        }
+        if args.page_by == Some(PageBy::Rows(0)) {
+            return Err(Error::ZeroPagination);
+        }
+        let save_location = match args.save_location {
+            Some(path) if path.is_dir() => {
+                Some(path.join(config.storage.unwrap_or_default().file_name()))
+            }
+            path => path,
+        };
+        let save_location = save_location.map(std::path::absolute).transpose()?;
+
+        let profile = ProfileConfig {
+            save_location,
+            table_style: args.table_style,
+            page_by: args.page_by,
+        };
+        config.profiles.insert(name.clone(), profile);
+        if args.switch {
+            config.profile = Some(name.clone());
+        }
+        self.save_config(config)?;
+        println!("Created profile `{name}` at {}", config.profile_path(Some(&name)).display());
+        if !args.switch {
+            println!("Use it with `arx --profile {name} ...` or `arx profile switch {name}`");
+        }
+        Ok(())
+    }
+
+    /// Removes the profile from the config and deletes its bookmarks and journal, the
+    /// snapshots next to them are kept.
+    fn delete_profile(&self, name: &str, yes: bool, config: &mut Config) -> Result<()> {
+        if name == DEFAULT_PROFILE {
+            return Err(Error::InvalidProfileName(name.to_string()));
+        }
+        if !config.profiles.contains_key(name) {
+            return Err(Error::UnknownProfile(name.to_string()));
+        }
+        if config.active_profile.as_deref() == Some(name) {
+            return Err(Error::ProfileInUse(name.to_string()));
+        }
+        let path = config.profile_path(Some(name));
+        let _lock = StoreLock::acquire(&path, true)?;
+        let count = storage::count(&path)?;
+        if !yes {
+            let question = format!(
+                "Delete profile `{name}` and its {count} bookmark(s) at {}? [y/n] ",
+                path.display()
+            );
+            if !prompt(&question)?.eq_ignore_ascii_case("y") {
+                return Ok(());
+            }
+        }
+
+        for file in [path.clone(), Journal::path(&path)] {
+            if file.exists() {
+                fs::remove_file(file)?;
+            }
+        }
+        config.profiles.remove(name);
+        if config.profile.as_deref() == Some(name) {
+            config.profile = None;
+        }
+        self.save_config(config)?;
+        println!("Deleted profile `{name}`");
+        Ok(())
+    }
+
+    /// Lists the profiles with their bookmark counts. The other profiles' stores are only
+    /// counted, under a shared lock, so another arx writing to them is never disturbed.
+    fn list_profiles(&self, config: &Config) -> Result<()> {
+        let mut names = vec![DEFAULT_PROFILE];
+        names.extend(config.profiles.keys().map(String::as_str));
+
+        let mut table = new_table(config);
+        table.set_header(header(&["", "profile", "bookmarks", "location"]));
+        for name in names {
+            let profile = (name != DEFAULT_PROFILE).then_some(name);
+            let path = config.profile_path(profile);
+            let active = config.active_profile.as_deref() == profile;
+            // the store in use is already loaded and locked by this run
+            let count = match active {
+                true => Ok(self.bookmarks.len()),
+                false if !path.exists() => Ok(0),
+                false => StoreLock::acquire(&path, false).and_then(|_lock| storage::count(&path)),
+            };
+            table.add_row(vec![
+                if active { "*" } else { "" }.to_string(),
+                name.to_string(),
+                count.map_or("?".to_string(), |count| count.to_string()),
+                path.display().to_string(),
+            ]);
+        }
+        println!("{table}");
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    command_types::{ExistingStore, StorageKind},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    journal::Journal,
+    utils::prompt,
+};
+
+impl BookmarkStore {
+    /// Moves the store and its journal to `target` and saves there from now on. Without a
+    /// file there is nothing to move, and bookmarks already at `target` are merged,
+    /// overwritten or left alone as `existing` says, asking when it isn't set.
+    pub fn relocate(
+        &mut self,
+        target: PathBuf,
+        config: &Config,
+        existing: Option<ExistingStore>,
+    ) -> Result<()> {
+        if target == self.path {
+            return Ok(());
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let source = self.path.clone();
+        let same_storage = StorageKind::of(&source) == StorageKind::of(&target);
+        match (source.exists(), target.exists()) {
+            (true, false) if same_storage => {
+                let saved = std::mem::take(&mut self.saved);
+                self.rebind(target);
+                move_file(&source, &self.path)?;
+                self.saved = saved;
+            }
+            (true, false) => {
+                // another storage can't take the file as it is, the bookmarks are
+                // written out anew
+                self.rebind(target);
+                self.save(config)?;
+                self.verify_copy()?;
+                fs::remove_file(&source)?;
+            }
+            (false, false) => self.rebind(target),
+            (false, true) => {
+                // nothing of ours to move, the bookmarks there become the store
+                *self = BookmarkStore::load_from(&target)?;
+                let count = self.bookmarks.len();
+                eprintln!("Using the {count} bookmark(s) at {}", target.display());
+                return Ok(());
+            }
+            (true, true) => {
+                let mut found = BookmarkStore::load_from(&target)?;
+                let existing = match existing {
+                    Some(existing) => existing,
+                    None => ask(&target, found.bookmarks.len())?,
+                };
+                match existing {
+                    ExistingStore::Abort => {
+                        return Err(Error::RelocationAborted(target.display().to_string()));
+                    }
+                    ExistingStore::Merge => {
+                        let added = self.merge(std::mem::take(&mut found.bookmarks));
+                        eprintln!("Added {added} bookmark(s) from {}", target.display());
+                    }
+                    ExistingStore::Overwrite => {}
+                }
+                // saving snapshots what was there before replacing it
+                self.path = target;
+                self.storage = found.storage;
+                self.saved = found.saved;
+                self.snapshot(config, true)?;
+                self.write()?;
+                fs::remove_file(&source)?;
+            }
+        }
+
+        let journal = Journal::path(&source);
+        if journal.exists() {
+            move_file(&journal, &Journal::path(&self.path))?;
+        }
+        Ok(())
+    }
+
+    /// Reads back what was just saved to another storage, so the original is only
+    /// removed once every bookmark arrived intact.
+    fn verify_copy(&mut self) -> Result<()> {
+        let stored = self.storage.query(None)?.len();
+        for bookmark in &self.bookmarks {
+            if self.storage.get(bookmark.id)?.as_ref() != Some(bookmark) {
+                return Err(Error::CopyMismatch(self.path.display().to_string()));
+            }
+        }
+        match stored == self.bookmarks.len() {
+            true => Ok(()),
+            false => Err(Error::CopyMismatch(self.path.display().to_string())),
+        }
+    }
+
+    /// Adds the bookmarks of `other` that aren't in the store yet under new IDs and
+    /// returns how many there were.
+    fn merge(&mut self, other: Vec<Bookmark>) -> usize {
+        let mut added = 0;
+        for mut bookmark in other {
+            if self.bookmarks.iter().any(|b| same(b, &bookmark)) {
+                continue;
+            }
+            bookmark.id = self.next_id;
+            self.next_id += 1;
+            self.bookmarks.push(bookmark);
+            added += 1;
+        }
+        added
+    }
+}
+
+/// Bookmarks are the same when their urls are, or their titles if neither has a url.
+fn same(a: &Bookmark, b: &Bookmark) -> bool {
+    match (&a.url, &b.url) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => a.title.eq_ignore_ascii_case(&b.title),
+        _ => false,
+    }
+}
+
+fn ask(target: &Path, count: usize) -> Result<ExistingStore> {
+    eprintln!("{} already has {count} bookmark(s).", target.display());
+    let answer = prompt("[m]erge them with yours, [o]verwrite them or [a]bort? ")?;
+    Ok(match answer.to_lowercase().as_str() {
+        "m" | "merge" => ExistingStore::Merge,
+        "o" | "overwrite" => ExistingStore::Overwrite,
+        _ => ExistingStore::Abort,
+    })
+}
+
+/// `fs::rename` only works within one filesystem, across them the file is copied over
+/// and then removed.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() == ErrorKind::CrossesDevices => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)?;
+            Ok(())
+        }
+        result => Ok(result?),
+    }
+}
@@ -0,0 +1,120 @@
+use comfy_table::{Attribute, Cell};
+use sublime_fuzzy::best_match;
+
+use crate::{
+    command_types::{PickArgs, SearchQuery},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    table::{header, new_table},
+    utils::prompt,
+};
+
+/// Most candidates offered in the pick list.
+const MAX_CANDIDATES: usize = 9;
+
+/// A bookmark picked out by a `SearchQuery`. `score` is only set for fuzzy queries,
+/// `picked` when the user chose it from the pick list.
+#[derive(Debug, Clone, Copy)]
+pub struct Match {
+    pub id: usize,
+    pub score: Option<isize>,
+    pub picked: bool,
+}
+
+impl BookmarkStore {
+    /// Resolves an ID or fuzzy query to the stable ID of a single bookmark. When several
+    /// bookmarks score close to the best match the user is asked to pick one, unless
+    /// `--first` or `--strict` was passed.
+    pub fn resolve(&self, query: &SearchQuery, pick: PickArgs, config: &Config) -> Result<Match> {
+        let query = match query {
+            SearchQuery::Id(id) => {
+                self.get(*id)?;
+                return Ok(Match { id: *id, score: None, picked: false });
+            }
+            SearchQuery::Range(start, end) => {
+                let ids = self.ids_in(*start, *end);
+                return match ids.as_slice() {
+                    [] => Err(Error::NoMatch(query.to_string())),
+                    [id] => Ok(Match { id: *id, score: None, picked: false }),
+                    _ => Err(Error::AmbiguousQuery(query.to_string(), ids.len())),
+                };
+            }
+            SearchQuery::Query(query) => query,
+        };
+        let candidates = close_matches(self.fuzzy_matches(query));
+        match candidates.as_slice() {
+            [] => Err(Error::NoMatch(query.clone())),
+            [best] => Ok(*best),
+            [best, ..] if pick.first => Ok(*best),
+            _ if pick.strict => Err(Error::AmbiguousQuery(query.clone(), candidates.len())),
+            _ => self.pick(&candidates, config),
+        }
+    }
+
+    /// IDs of the bookmarks from `start` to `end`, both included.
+    pub fn ids_in(&self, start: usize, end: usize) -> Vec<usize> {
+        self.bookmarks
+            .iter()
+            .map(|b| b.id)
+            .filter(|id| (start..=end).contains(id))
+            .collect()
+    }
+
+    /// All bookmarks whose title matches `query`, best match first.
+    pub fn fuzzy_matches(&self, query: &str) -> Vec<Match> {
+        let mut matches: Vec<Match> = self
+            .bookmarks
+            .iter()
+            .filter_map(|b| {
+                score(query, b).map(|score| Match { id: b.id, score: Some(score), picked: false })
+            })
+            .collect();
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches
+    }
+
+    fn pick(&self, candidates: &[Match], config: &Config) -> Result<Match> {
+        let mut table = new_table(config);
+        table.set_header(header(&["#", "ID", "name", "category", "score"]));
+        for (index, candidate) in candidates.iter().enumerate() {
+            let bookmark = self.get(candidate.id)?;
+            table.add_row(vec![
+                Cell::new(index + 1).add_attribute(Attribute::Bold),
+                Cell::new(bookmark.id),
+                Cell::new(&bookmark.title),
+                Cell::new(&bookmark.category),
+                Cell::new(candidate.score.unwrap_or_default()),
+            ]);
+        }
+        eprintln!("{table}");
+
+        let input = prompt(&format!(
+            "Several bookmarks match, pick one [1-{}] (empty to cancel) ",
+            candidates.len()
+        ))?;
+        match input.parse::<usize>() {
+            Ok(n) if (1..=candidates.len()).contains(&n) => {
+                Ok(Match { picked: true, ..candidates[n - 1] })
+            }
+            _ => Err(Error::NothingPicked),
+        }
+    }
+}
+
+fn score(query: &str, bookmark: &Bookmark) -> Option<isize> {
+    best_match(query, &bookmark.title).map(|m| m.score())
+}
+
+/// Keeps the matches scoring within 20% of the best one.
+fn close_matches(matches: Vec<Match>) -> Vec<Match> {
+    let Some(best) = matches.first().and_then(|m| m.score) else {
+        return matches;
+    };
+    let threshold = best - best.abs() / 5;
+    matches
+        .into_iter()
+        .take_while(|m| m.score.is_some_and(|score| score >= threshold))
+        .take(MAX_CANDIDATES)
+        .collect()
+}
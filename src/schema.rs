@@ -0,0 +1,74 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+
+use crate::errors::{Error, Result};
+
+/// Version of the bookmarks file written by this arx, bump it with every new migration.
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// The migration at index `n` takes a file from version `n` to `n + 1`.
+const MIGRATIONS: [fn(&mut Value); SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Files from before the version was recorded count as version 0.
+pub fn version_of(data: &Value) -> u64 {
+    data.get("schema_version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Brings `data` up to `SCHEMA_VERSION`, refusing files from a newer arx.
+pub fn migrate(data: &mut Value) -> Result<()> {
+    let version = version_of(data);
+    if version > SCHEMA_VERSION {
+        return Err(Error::NewerSchema(version, SCHEMA_VERSION));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(data);
+    }
+    if let Some(store) = data.as_object_mut() {
+        store.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    }
+    Ok(())
+}
+
+/// Copies the file at `path` aside before it is migrated from `version`, once per version
+/// so the original survives later runs.
+pub fn backup(path: &Path, version: u64) -> Result<()> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("bookmarks.json");
+    let backup = path.with_file_name(format!("{file_name}.v{version}.bak"));
+    if !backup.exists() {
+        fs::copy(path, &backup)?;
+        eprintln!(
+            "Updating your bookmarks to a newer format, the old file is kept at {}",
+            backup.display()
+        );
+    }
+    Ok(())
+}
+
+fn bookmarks(data: &mut Value) -> impl Iterator<Item = &mut serde_json::Map<String, Value>> {
+    data.get_mut("bookmarks")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+/// Categories were variants of a fixed enum written as "Book", and bookmarks had no
+/// completion time or status history.
+fn v0_to_v1(data: &mut Value) {
+    for bookmark in bookmarks(data) {
+        if let Some(Value::String(category)) = bookmark.get_mut("category") {
+            *category = category.to_lowercase();
+        }
+        bookmark.entry("completed_at").or_insert(Value::Null);
+        bookmark.entry("history").or_insert(Value::Array(Vec::new()));
+    }
+}
+
+/// Bookmarks had no priority or due date.
+fn v1_to_v2(data: &mut Value) {
+    for bookmark in bookmarks(data) {
+        bookmark.entry("priority").or_insert(Value::Null);
+        bookmark.entry("due").or_insert(Value::Null);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{io::IsTerminal, ops::Range};
+
+use comfy_table::CellAlignment;
+
+use crate::{
+    Cell,
+    command_types::{Column, OutputFormat, SearchArgs},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::Result,
+    output::report,
+    table::{column_cell, columns_table, header, highlight, title_cell},
+};
+
+/// Characters of context kept around the first match in notes.
+const SNIPPET_CONTEXT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    Tags,
+    Url,
+    Notes,
+}
+
+impl Field {
+    fn weight(self) -> usize {
+        match self {
+            Field::Title => 4,
+            Field::Tags => 3,
+            Field::Url => 2,
+            Field::Notes => 1,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Title => "title",
+            Field::Tags => "tags",
+            Field::Url => "url",
+            Field::Notes => "notes",
+        }
+    }
+}
+
+/// The text of one field with the byte ranges that matched the query.
+struct FieldMatch {
+    field: Field,
+    text: String,
+    spans: Vec<Range<usize>>,
+}
+
+struct Hit<'a> {
+    bookmark: &'a Bookmark,
+    score: usize,
+    fields: Vec<FieldMatch>,
+}
+
+impl Hit<'_> {
+    fn title_match(&self) -> Option<&FieldMatch> {
+        self.fields.iter().find(|m| m.field == Field::Title)
+    }
+}
+
+impl BookmarkStore {
+    pub fn search(&self, args: SearchArgs, config: &Config) -> Result<()> {
+        let terms: Vec<String> = args
+            .query
+            .iter()
+            .flat_map(|q| q.split_whitespace())
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut hits: Vec<Hit> = self
+            .bookmarks
+            .iter()
+            .filter(|b| args.all || (!b.status.is_closed() && !b.hidden))
+            .filter_map(|b| search_bookmark(b, &terms))
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.bookmark.id.cmp(&b.bookmark.id)));
+        if let Some(limit) = args.limit {
+            hits.truncate(limit);
+        }
+        if config.output != OutputFormat::Table {
+            let bookmarks: Vec<&Bookmark> = hits.iter().map(|hit| hit.bookmark).collect();
+            return report(config.output, &bookmarks, None);
+        }
+        if hits.is_empty() {
+            println!("No bookmarks found.");
+            return Ok(());
+        }
+
+        // the columns of `arx ls` and what matched besides the title in one more
+        let full_urls = config.full_urls.unwrap_or(false) || !std::io::stdout().is_terminal();
+        let columns = config.columns.clone().unwrap_or_else(|| Column::DEFAULT.to_vec());
+        let shows_title = columns.contains(&Column::Title);
+        let mut table = columns_table(config, &columns, full_urls);
+        let mut names: Vec<&str> = columns.iter().map(|c| c.name()).collect();
+        names.push("match");
+        table.set_header(header(&names));
+
+        for hit in &hits {
+            let mut row: Vec<Cell> = columns
+                .iter()
+                .map(|column| match (column, hit.title_match()) {
+                    (Column::Title, Some(m)) => {
+                        title_cell(hit.bookmark, &highlight(&m.text, &m.spans))
+                    }
+                    _ => column_cell(*column, hit.bookmark, config, full_urls),
+                })
+                .collect();
+            let other_fields: Vec<String> = hit
+                .fields
+                .iter()
+                .filter(|m| !shows_title || m.field != Field::Title)
+                .map(|m| format!("{}: {}", m.field.name(), highlight(&m.text, &m.spans)))
+                .collect();
+            row.push(Cell::new(other_fields.join("\n")).set_alignment(CellAlignment::Left));
+            table.add_row(row);
+        }
+
+        println!("{table}");
+        println!("Found {} matching bookmark(s)", hits.len());
+        Ok(())
+    }
+}
+
+/// Scores `bookmark` against all `terms`, every term has to be found in at least one field.
+fn search_bookmark<'a>(bookmark: &'a Bookmark, terms: &[String]) -> Option<Hit<'a>> {
+    let tags = bookmark.tags.as_ref().map(|tags| tags.join(", "));
+    let texts = [
+        (Field::Title, Some(&bookmark.title)),
+        (Field::Tags, tags.as_ref()),
+        (Field::Url, bookmark.url.as_ref()),
+        (Field::Notes, bookmark.notes.as_ref()),
+    ];
+
+    let mut score = 0;
+    let mut fields: Vec<FieldMatch> = Vec::new();
+    for term in terms {
+        let mut found = false;
+        for (field, text) in texts {
+            let Some(text) = text else { continue };
+            let spans = find_all(text, term);
+            if spans.is_empty() {
+                continue;
+            }
+            found = true;
+            let at_word_start = spans
+                .iter()
+                .any(|s| text[..s.start].chars().last().is_none_or(|c| !c.is_alphanumeric()));
+            score += field.weight() * (spans.len().min(3) + usize::from(at_word_start));
+            match fields.iter_mut().find(|m| m.field == field) {
+                Some(m) => m.spans.extend(spans),
+                None => fields.push(FieldMatch { field, text: text.clone(), spans }),
+            }
+        }
+        if !found {
+            return None;
+        }
+    }
+
+    for m in fields.iter_mut() {
+        merge_spans(&mut m.spans);
+        if m.field == Field::Notes {
+            snippet(m);
+        }
+    }
+    fields.sort_by_key(|m| m.field.weight());
+    fields.reverse();
+    Some(Hit { bookmark, score, fields })
+}
+
+/// Case-insensitive byte ranges of every occurrence of `term` (already lowercase) in `text`.
+fn find_all(text: &str, term: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    if term.is_empty() {
+        return spans;
+    }
+    let mut start = 0;
+    while start < text.len() {
+        let mut chars = text[start..].char_indices().flat_map(|(i, c)| {
+            let len = c.len_utf8();
+            c.to_lowercase().map(move |l| (i + len, l))
+        });
+        let mut end = None;
+        for expected in term.chars() {
+            match chars.next() {
+                Some((i, c)) if c == expected => end = Some(i),
+                _ => {
+                    end = None;
+                    break;
+                }
+            }
+        }
+        let step = text[start..].chars().next().map_or(1, char::len_utf8);
+        match end {
+            Some(end) => {
+                spans.push(start..start + end);
+                start += end;
+            }
+            None => start += step,
+        }
+    }
+    spans
+}
+
+fn merge_spans(spans: &mut Vec<Range<usize>>) {
+    spans.sort_by_key(|s| s.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+    for span in spans.drain(..) {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+    *spans = merged;
+}
+
+/// Cuts long notes down to the context around the first match.
+fn snippet(m: &mut FieldMatch) {
+    let first = m.spans[0].clone();
+    let start = m.text[..first.start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let end = m.text[first.end..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(m.text.len(), |(i, _)| first.end + i);
+
+    let mut text = String::new();
+    if start > 0 {
+        text.push('…');
+    }
+    let offset = text.len();
+    text.push_str(&m.text[start..end]);
+    if end < m.text.len() {
+        text.push('…');
+    }
+    m.spans = m
+        .spans
+        .iter()
+        .filter(|s| s.start >= start && s.end <= end)
+        .map(|s| s.start - start + offset..s.end - start + offset)
+        .collect();
+    m.text = text.replace('\n', " ");
+}
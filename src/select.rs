@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use comfy_table::{Cell, Table};
+
+use crate::{
+    command_types::{FilterArgs, OutputFormat, SearchQuery, SelectionArgs},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    output::report,
+    table::{category_cell, header, new_table, status_cell, title_cell},
+};
+
+/// Stable IDs of the bookmarks a command acts on.
+pub struct Selection {
+    pub ids: Vec<usize>,
+    /// Set when bookmarks were chosen by a fuzzy query the user didn't pick from a list
+    /// or only by filters, so destructive commands should ask first.
+    pub unconfirmed: bool,
+}
+
+impl BookmarkStore {
+    /// Resolves IDs, ranges and fuzzy queries and narrows them down with the filters.
+    /// Without any queries the filters alone choose the bookmarks, leaving done and
+    /// hidden ones out like `arx ls` does unless `--all` is set.
+    pub fn select(&self, args: &SelectionArgs, config: &Config) -> Result<Selection> {
+        if args.queries.is_empty() && !args.has_filter() {
+            return Err(Error::EmptySelection);
+        }
+
+        let mut ids = Vec::new();
+        let mut unconfirmed = args.queries.is_empty();
+        for query in &args.queries {
+            match query {
+                SearchQuery::Id(id) => {
+                    self.get(*id)?;
+                    ids.push(*id);
+                }
+                SearchQuery::Range(start, end) => {
+                    let in_range = self.ids_in(*start, *end);
+                    if in_range.is_empty() {
+                        return Err(Error::NoMatch(query.to_string()));
+                    }
+                    ids.extend(in_range);
+                }
+                SearchQuery::Query(_) => {
+                    let found = self.resolve(query, args.pick, config)?;
+                    unconfirmed |= !found.picked;
+                    ids.push(found.id);
+                }
+            }
+        }
+
+        if args.has_filter() || args.queries.is_empty() {
+            let filter = FilterArgs {
+                category: args.category.clone(),
+                tag: args.tag.clone(),
+                status: args.status.clone(),
+                expr: args.expr.clone(),
+                // bookmarks asked for by ID are never left out for being done or hidden
+                all: args.all || !args.queries.is_empty(),
+            };
+            let matching: Vec<usize> =
+                self.filtered(&filter, None)?.iter().map(|b| b.id).collect();
+            if args.queries.is_empty() {
+                ids = matching;
+            } else {
+                ids.retain(|id| matching.contains(id));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(*id));
+        if ids.is_empty() {
+            return Err(Error::NothingSelected);
+        }
+        Ok(Selection { ids, unconfirmed })
+    }
+
+    /// Prints the bookmarks `--dry-run` would have changed.
+    pub fn preview(&self, selection: &Selection, config: &Config) -> Result<()> {
+        let bookmarks = self.selected(selection)?;
+        if config.output != OutputFormat::Table {
+            return report(config.output, &bookmarks, None);
+        }
+        println!("{}", selection_table(&bookmarks, config));
+        println!("{} bookmark(s) selected, nothing was changed", bookmarks.len());
+        Ok(())
+    }
+
+    pub fn selected(&self, selection: &Selection) -> Result<Vec<&Bookmark>> {
+        selection.ids.iter().map(|id| self.get(*id)).collect()
+    }
+}
+
+/// A short table of bookmarks, used to show what a bulk command is about to change.
+pub fn selection_table(bookmarks: &[&Bookmark], config: &Config) -> Table {
+    let mut table = new_table(config);
+    table.set_header(header(&["ID", "name", "category", "status"]));
+    for bookmark in bookmarks {
+        table.add_row(vec![
+            Cell::new(bookmark.id),
+            title_cell(bookmark, &bookmark.title),
+            category_cell(bookmark, config),
+            status_cell(bookmark),
+        ]);
+    }
+    table
+}
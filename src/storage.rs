@@ -0,0 +1,540 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    command_types::{MigrateStorageArgs, Priority, Status, StorageKind},
+    config::Config,
+    data::{Bookmark, BookmarkStore},
+    errors::{Error, Result},
+    filter::Filter,
+    schema::{self, SCHEMA_VERSION},
+    utils::write_atomic,
+};
+
+/// Where a store keeps its bookmarks. Changes are collected by `insert`, `update` and
+/// `delete` and only written out by `commit`, so a backend can write just what changed.
+pub trait Storage {
+    /// The next free ID and every bookmark, ordered by ID.
+    fn load(&mut self) -> Result<(usize, Vec<Bookmark>)>;
+    fn next_id(&mut self) -> Result<usize>;
+    fn get(&mut self, id: usize) -> Result<Option<Bookmark>>;
+    fn insert(&mut self, bookmark: &Bookmark) -> Result<()>;
+    fn update(&mut self, bookmark: &Bookmark) -> Result<()>;
+    fn delete(&mut self, id: usize) -> Result<()>;
+    /// Bookmarks matching `filter`, all of them without one, ordered by ID.
+    fn query(&mut self, filter: Option<&Filter>) -> Result<Vec<Bookmark>>;
+    fn commit(&mut self, next_id: usize) -> Result<()>;
+    /// Copies the store as last committed to `to`.
+    fn snapshot(&mut self, to: &Path) -> Result<()>;
+}
+
+impl StorageKind {
+    /// The backend of the file at `path`, told apart by its extension.
+    pub fn of(path: &Path) -> StorageKind {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => StorageKind::Sqlite,
+            _ => StorageKind::Json,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StorageKind::Json => "json",
+            StorageKind::Sqlite => "sqlite",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            StorageKind::Json => "bookmarks.json",
+            StorageKind::Sqlite => "bookmarks.db",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            StorageKind::Json => "json",
+            StorageKind::Sqlite => "db",
+        }
+    }
+}
+
+/// Opens the store at `path` with the backend its extension calls for. Nothing is read
+/// or created until it is loaded or committed.
+pub fn open(path: &Path) -> Box<dyn Storage> {
+    match StorageKind::of(path) {
+        StorageKind::Json => Box::new(JsonStorage { path: path.to_path_buf(), file: None }),
+        StorageKind::Sqlite => Box::new(SqliteStorage {
+            path: path.to_path_buf(),
+            conn: None,
+            columns: SQLITE_COLUMNS.to_string(),
+        }),
+    }
+}
+
+/// Reads a snapshot of a `kind` store as it is, without setting the original aside when
+/// its format is migrated.
+pub fn read_snapshot(kind: StorageKind, path: &Path) -> Result<(usize, Vec<Bookmark>)> {
+    match kind {
+        StorageKind::Json => {
+            let file = JsonFile::parse(serde_json::from_str(&fs::read_to_string(path)?)?)?;
+            Ok((file.next_id, file.bookmarks))
+        }
+        StorageKind::Sqlite => SqliteStorage::read_only(path)?.load(),
+    }
+}
+
+/// Counts the bookmarks at `path` without migrating or otherwise changing the store, 0 if
+/// there is none yet.
+pub fn count(path: &Path) -> Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    match StorageKind::of(path) {
+        StorageKind::Json => {
+            let data: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+            Ok(data.get("bookmarks").and_then(Value::as_array).map_or(0, Vec::len))
+        }
+        StorageKind::Sqlite => {
+            let storage = SqliteStorage::read_only(path)?;
+            let conn = storage.conn.as_ref().expect("opened read-only");
+            let count: i64 =
+                conn.query_row("SELECT COUNT(*) FROM bookmarks", [], |row| row.get(0))?;
+            Ok(count as usize)
+        }
+    }
+}
+
+/// The whole store as one JSON document, rewritten on every commit.
+struct JsonStorage {
+    path: PathBuf,
+    /// The file as last loaded plus the uncommitted changes.
+    file: Option<JsonFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonFile {
+    /// Version of the file format, see `schema.rs`.
+    #[serde(default)]
+    schema_version: u64,
+    next_id: usize,
+    bookmarks: Vec<Bookmark>,
+}
+
+impl JsonFile {
+    /// Reads a store of any earlier schema version, migrating it first.
+    fn parse(mut data: Value) -> Result<JsonFile> {
+        schema::migrate(&mut data)?;
+        let mut file: JsonFile = serde_json::from_value(data)?;
+        file.repair_ids();
+        Ok(file)
+    }
+
+    /// Older versions renumbered every bookmark to its index after a removal, which
+    /// could leave `next_id` pointing at an ID that is still in use. IDs are kept as
+    /// they are; only duplicates get a fresh ID and `next_id` is moved past the highest one.
+    fn repair_ids(&mut self) {
+        let mut seen = HashSet::new();
+        for bookmark in self.bookmarks.iter_mut() {
+            if !seen.insert(bookmark.id) {
+                bookmark.id = usize::MAX;
+            }
+        }
+        let max_id = seen.iter().max().copied().unwrap_or(0);
+        self.next_id = self.next_id.max(max_id + 1);
+        for bookmark in self.bookmarks.iter_mut().filter(|b| b.id == usize::MAX) {
+            bookmark.id = self.next_id;
+            self.next_id += 1;
+        }
+    }
+
+    fn position(&self, id: usize) -> std::result::Result<usize, usize> {
+        self.bookmarks.binary_search_by_key(&id, |b| b.id)
+    }
+}
+
+impl JsonStorage {
+    fn file(&mut self) -> Result<&mut JsonFile> {
+        if self.file.is_none() {
+            self.load()?;
+        }
+        Ok(self.file.as_mut().expect("loaded above"))
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load(&mut self) -> Result<(usize, Vec<Bookmark>)> {
+        let file = match self.path.exists() {
+            true => {
+                let data: Value = serde_json::from_str(&fs::read_to_string(&self.path)?)?;
+                let version = schema::version_of(&data);
+                if version < SCHEMA_VERSION {
+                    schema::backup(&self.path, version)?;
+                }
+                JsonFile::parse(data)?
+            }
+            false => JsonFile { schema_version: SCHEMA_VERSION, next_id: 1, bookmarks: Vec::new() },
+        };
+        let loaded = (file.next_id, file.bookmarks.clone());
+        self.file = Some(file);
+        Ok(loaded)
+    }
+
+    fn next_id(&mut self) -> Result<usize> {
+        Ok(self.file()?.next_id)
+    }
+
+    fn get(&mut self, id: usize) -> Result<Option<Bookmark>> {
+        let file = self.file()?;
+        Ok(file.position(id).ok().map(|index| file.bookmarks[index].clone()))
+    }
+
+    fn insert(&mut self, bookmark: &Bookmark) -> Result<()> {
+        let file = self.file()?;
+        match file.position(bookmark.id) {
+            Ok(index) => file.bookmarks[index] = bookmark.clone(),
+            Err(index) => file.bookmarks.insert(index, bookmark.clone()),
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, bookmark: &Bookmark) -> Result<()> {
+        self.insert(bookmark)
+    }
+
+    fn delete(&mut self, id: usize) -> Result<()> {
+        let file = self.file()?;
+        if let Ok(index) = file.position(id) {
+            file.bookmarks.remove(index);
+        }
+        Ok(())
+    }
+
+    fn query(&mut self, filter: Option<&Filter>) -> Result<Vec<Bookmark>> {
+        let file = self.file()?;
+        let matches = |b: &&Bookmark| filter.is_none_or(|f| f.matches(b));
+        Ok(file.bookmarks.iter().filter(matches).cloned().collect())
+    }
+
+    fn commit(&mut self, next_id: usize) -> Result<()> {
+        let path = self.path.clone();
+        let file = self.file()?;
+        file.next_id = next_id;
+        file.schema_version = SCHEMA_VERSION;
+        write_atomic(&path, serde_json::to_string(file)?.as_bytes())
+    }
+
+    fn snapshot(&mut self, to: &Path) -> Result<()> {
+        fs::copy(&self.path, to)?;
+        Ok(())
+    }
+}
+
+/// One row per bookmark in an SQLite database, so a command writes only the rows it
+/// changed. Tags and the status history are kept as JSON.
+struct SqliteStorage {
+    path: PathBuf,
+    conn: Option<Connection>,
+    /// The columns to select, `NULL` for those a read-only database from an older arx
+    /// doesn't have yet.
+    columns: String,
+}
+
+const SQLITE_TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS bookmarks (
+        id INTEGER PRIMARY KEY,
+        title TEXT NOT NULL,
+        category TEXT NOT NULL,
+        url TEXT,
+        tags TEXT,
+        notes TEXT,
+        status TEXT NOT NULL,
+        hidden INTEGER NOT NULL,
+        created_at TEXT NOT NULL,
+        completed_at TEXT,
+        history TEXT NOT NULL,
+        priority TEXT,
+        due TEXT
+    );";
+
+const SQLITE_COLUMNS: &str = "id, title, category, url, tags, notes, status, hidden, \
+                              created_at, completed_at, history, priority, due";
+
+/// The statement at index `n` takes a database from schema version `n + 1` to `n + 2`,
+/// like the migrations of JSON files in `schema.rs`.
+const SQLITE_MIGRATIONS: [&str; SCHEMA_VERSION as usize - 1] =
+    ["ALTER TABLE bookmarks ADD COLUMN priority TEXT; ALTER TABLE bookmarks ADD COLUMN due TEXT;"];
+
+impl SqliteStorage {
+    /// Opens the database at `path` without creating, migrating or otherwise changing it,
+    /// e.g. to read a snapshot.
+    fn read_only(path: &Path) -> Result<SqliteStorage> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version: i64 = conn
+            .query_row("SELECT value FROM meta WHERE key = 'schema_version'", [], |row| {
+                row.get(0)
+            })
+            .optional()?
+            .unwrap_or(1);
+        if version as u64 > SCHEMA_VERSION {
+            return Err(Error::NewerSchema(version as u64, SCHEMA_VERSION));
+        }
+        let existing = conn
+            .prepare("SELECT name FROM pragma_table_info('bookmarks')")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+        let columns: Vec<&str> = SQLITE_COLUMNS
+            .split(',')
+            .map(str::trim)
+            .map(|column| if existing.contains(column) { column } else { "NULL" })
+            .collect();
+        Ok(SqliteStorage { path: path.to_path_buf(), conn: Some(conn), columns: columns.join(", ") })
+    }
+
+    /// The open database, created along with its tables on first use.
+    fn conn(&mut self) -> Result<&mut Connection> {
+        if self.conn.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let conn = Connection::open(&self.path)?;
+            conn.execute_batch(SQLITE_TABLES)?;
+            let version: Option<i64> = conn
+                .query_row("SELECT value FROM meta WHERE key = 'schema_version'", [], |row| {
+                    row.get(0)
+                })
+                .optional()?;
+            match version {
+                Some(version) if version as u64 > SCHEMA_VERSION => {
+                    return Err(Error::NewerSchema(version as u64, SCHEMA_VERSION));
+                }
+                Some(version) if (version as u64) < SCHEMA_VERSION => {
+                    schema::backup(&self.path, version as u64)?;
+                    conn.execute_batch("BEGIN IMMEDIATE")?;
+                    for migration in &SQLITE_MIGRATIONS[version as usize - 1..] {
+                        conn.execute_batch(migration)?;
+                    }
+                    conn.execute(
+                        "UPDATE meta SET value = ?1 WHERE key = 'schema_version'",
+                        [SCHEMA_VERSION as i64],
+                    )?;
+                    conn.execute_batch("COMMIT")?;
+                }
+                Some(_) => {}
+                None => {
+                    conn.execute(
+                        "INSERT INTO meta (key, value) VALUES ('schema_version', ?1)",
+                        [SCHEMA_VERSION as i64],
+                    )?;
+                }
+            }
+            self.conn = Some(conn);
+        }
+        Ok(self.conn.as_mut().expect("opened above"))
+    }
+
+    /// The database with a transaction open for the changes up to the next commit.
+    fn writer(&mut self) -> Result<&mut Connection> {
+        let conn = self.conn()?;
+        if conn.is_autocommit() {
+            conn.execute_batch("BEGIN IMMEDIATE")?;
+        }
+        Ok(conn)
+    }
+
+    fn select(&mut self, condition: &str, params: impl rusqlite::Params) -> Result<Vec<Bookmark>> {
+        let sql = format!("SELECT {} FROM bookmarks {condition} ORDER BY id", self.columns);
+        let conn = self.conn()?;
+        let mut statement = conn.prepare(&sql)?;
+        let rows = statement.query_map(params, |row| Ok(from_row(row)))?;
+        rows.map(|row| row?).collect()
+    }
+
+    fn write(&mut self, verb: &str, bookmark: &Bookmark) -> Result<()> {
+        let sql = format!(
+            "{verb} INTO bookmarks ({SQLITE_COLUMNS}) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"
+        );
+        let tags = bookmark.tags.as_ref().map(serde_json::to_string).transpose()?;
+        self.writer()?.execute(
+            &sql,
+            params![
+                bookmark.id as i64,
+                bookmark.title,
+                bookmark.category.to_string(),
+                bookmark.url,
+                tags,
+                bookmark.notes,
+                bookmark.status.name(),
+                bookmark.hidden,
+                bookmark.created_at,
+                bookmark.completed_at,
+                serde_json::to_string(&bookmark.history)?,
+                bookmark.priority.map(Priority::name),
+                bookmark.due,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+fn from_row(row: &Row) -> Result<Bookmark> {
+    let id: i64 = row.get(0)?;
+    let category: String = row.get(2)?;
+    let tags: Option<String> = row.get(4)?;
+    let status: String = row.get(6)?;
+    let history: String = row.get(10)?;
+    let priority = match row.get::<_, Option<String>>(11)? {
+        Some(name) => Some(
+            <Priority as clap::ValueEnum>::from_str(&name, true)
+                .map_err(|_| Error::UnknownPriority(name))?,
+        ),
+        None => None,
+    };
+    Ok(Bookmark {
+        id: id as usize,
+        title: row.get(1)?,
+        category: category.parse()?,
+        url: row.get(3)?,
+        tags: tags.as_deref().map(serde_json::from_str).transpose()?,
+        notes: row.get(5)?,
+        status: <Status as clap::ValueEnum>::from_str(&status, true)
+            .map_err(|_| Error::UnknownStatus(status))?,
+        hidden: row.get(7)?,
+        created_at: row.get(8)?,
+        completed_at: row.get(9)?,
+        history: serde_json::from_str(&history)?,
+        priority,
+        due: row.get(12)?,
+    })
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<(usize, Vec<Bookmark>)> {
+        // reading shouldn't leave an empty database behind
+        if self.conn.is_none() && !self.path.exists() {
+            return Ok((1, Vec::new()));
+        }
+        let bookmarks = self.select("", [])?;
+        Ok((self.next_id()?, bookmarks))
+    }
+
+    fn next_id(&mut self) -> Result<usize> {
+        if self.conn.is_none() && !self.path.exists() {
+            return Ok(1);
+        }
+        let conn = self.conn()?;
+        let next_id: Option<i64> = conn
+            .query_row("SELECT value FROM meta WHERE key = 'next_id'", [], |row| row.get(0))
+            .optional()?;
+        let max_id: Option<i64> =
+            conn.query_row("SELECT MAX(id) FROM bookmarks", [], |row| row.get(0))?;
+        Ok(next_id.unwrap_or(1).max(max_id.unwrap_or(0) + 1) as usize)
+    }
+
+    fn get(&mut self, id: usize) -> Result<Option<Bookmark>> {
+        if self.conn.is_none() && !self.path.exists() {
+            return Ok(None);
+        }
+        Ok(self.select("WHERE id = ?1", [id as i64])?.pop())
+    }
+
+    fn insert(&mut self, bookmark: &Bookmark) -> Result<()> {
+        self.write("INSERT", bookmark)
+    }
+
+    fn update(&mut self, bookmark: &Bookmark) -> Result<()> {
+        self.write("REPLACE", bookmark)
+    }
+
+    fn delete(&mut self, id: usize) -> Result<()> {
+        self.writer()?.execute("DELETE FROM bookmarks WHERE id = ?1", [id as i64])?;
+        Ok(())
+    }
+
+    fn query(&mut self, filter: Option<&Filter>) -> Result<Vec<Bookmark>> {
+        if self.conn.is_none() && !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        // only what the columns can tell is left to SQLite, the rest is matched row by row
+        let (condition, params) = match filter.and_then(Filter::sql) {
+            Some((condition, params)) => (format!("WHERE {condition}"), params),
+            None => (String::new(), Vec::new()),
+        };
+        let bookmarks = self.select(&condition, params_from_iter(params))?;
+        Ok(bookmarks.into_iter().filter(|b| filter.is_none_or(|f| f.matches(b))).collect())
+    }
+
+    fn commit(&mut self, next_id: usize) -> Result<()> {
+        let conn = self.writer()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('next_id', ?1)",
+            [next_id as i64],
+        )?;
+        conn.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    fn snapshot(&mut self, to: &Path) -> Result<()> {
+        // a consistent copy even while another reader has the database open
+        self.conn()?.execute("VACUUM INTO ?1", [to.to_string_lossy()])?;
+        Ok(())
+    }
+}
+
+impl BookmarkStore {
+    /// Points to `arx migrate-storage` when the config asks for another backend than the
+    /// store is kept in. `storage` only decides the kind of stores that don't exist yet.
+    pub fn check_storage(&self, config: &Config) {
+        let found = StorageKind::of(&self.path);
+        if let Some(wanted) = config.storage
+            && wanted != found
+            && self.path.exists()
+        {
+            eprintln!(
+                "Your bookmarks at {} are kept in {}, not {} as configured. \
+                 Run `arx migrate-storage --to {}` to convert them.",
+                self.path.display(),
+                found.name(),
+                wanted.name(),
+                wanted.name()
+            );
+        }
+    }
+
+    /// Copies the bookmarks into a store of another kind next to the current one and
+    /// uses that from now on, new stores get the kind too.
+    pub fn migrate_storage(&mut self, args: MigrateStorageArgs, config: &mut Config) -> Result<()> {
+        if StorageKind::of(&self.path) != args.to {
+            let target = self.path.with_extension(args.to.extension());
+            self.relocate(target, config, args.on_existing)?;
+            // stores in the data directory are found by their file name, others are
+            // pointed to by the config
+            if config.data_dir.is_none() {
+                match config.profile_config_mut() {
+                    Some(profile) => {
+                        if profile.save_location.is_some() {
+                            profile.save_location = Some(self.path.clone());
+                        }
+                    }
+                    None => config.save_location = self.path.clone(),
+                }
+            }
+        }
+        config.storage = Some(args.to);
+        self.save_config(config)?;
+        println!("{} bookmark(s) are stored at {}", self.bookmarks.len(), self.path.display());
+        Ok(())
+    }
+}
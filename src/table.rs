@@ -0,0 +1,188 @@
+use std::{io::IsTerminal, ops::Range};
+
+use chrono::{DateTime, Local, Utc};
+use comfy_table::{
+    Attribute, Cell, CellAlignment, Color, ColumnConstraint, ContentArrangement, Table,
+    presets::UTF8_FULL,
+};
+use terminal_link::Link;
+use terminal_size::{Height, terminal_size};
+
+use crate::{
+    command_types::{Column, Priority, Status},
+    config::Config,
+    data::Bookmark,
+};
+
+/// A table using the configured style.
+pub fn new_table(config: &Config) -> Table {
+    let mut table = Table::new();
+    table.load_preset(config.table_style().map(|s| s.to_comfy_style()).unwrap_or(UTF8_FULL));
+    table
+}
+
+pub fn header(names: &[&str]) -> Vec<Cell> {
+    names
+        .iter()
+        .map(|name| Cell::new(name).fg(Color::Yellow).add_attribute(Attribute::Bold))
+        .collect()
+}
+
+/// An empty table with a header for `columns`. Long text columns share what is left of
+/// the terminal width, the others are as wide as their content.
+pub fn columns_table(config: &Config, columns: &[Column], full_urls: bool) -> Table {
+    let mut table = new_table(config);
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(header(&columns.iter().map(|c| c.name()).collect::<Vec<_>>()));
+    for (index, column) in columns.iter().enumerate() {
+        let grows = match column {
+            Column::Title | Column::Tags | Column::Notes => true,
+            Column::Url => full_urls,
+            Column::Id
+            | Column::Category
+            | Column::Status
+            | Column::Created
+            | Column::Started
+            | Column::Completed
+            | Column::Priority
+            | Column::Due => false,
+        };
+        let table_column = table.column_mut(index).expect("column exists");
+        if !grows {
+            table_column.set_constraint(ColumnConstraint::ContentWidth);
+        }
+        if matches!(
+            column,
+            Column::Category
+                | Column::Status
+                | Column::Created
+                | Column::Started
+                | Column::Completed
+                | Column::Priority
+                | Column::Due
+        ) {
+            table_column.set_cell_alignment(CellAlignment::Center);
+        }
+    }
+    table
+}
+
+pub fn column_cell(
+    column: Column,
+    bookmark: &Bookmark,
+    config: &Config,
+    full_urls: bool,
+) -> Cell {
+    match column {
+        Column::Id => Cell::new(bookmark.id),
+        Column::Title => title_cell(bookmark, &bookmark.title),
+        Column::Category => category_cell(bookmark, config),
+        Column::Tags => Cell::new(bookmark.tags.as_deref().unwrap_or_default().join(", ")),
+        Column::Status => status_cell(bookmark),
+        Column::Url => url_cell(bookmark, full_urls),
+        Column::Created => date_cell(Some(bookmark.created_at)),
+        Column::Started => date_cell(bookmark.started_at()),
+        Column::Completed => date_cell(bookmark.completed_at),
+        Column::Priority => priority_cell(bookmark),
+        Column::Due => match bookmark.due {
+            Some(due) => Cell::new(due.format("%Y-%m-%d")),
+            None => Cell::new("━━"),
+        },
+        Column::Notes => {
+            Cell::new(bookmark.notes.as_deref().unwrap_or("-")).set_alignment(CellAlignment::Left)
+        }
+    }
+}
+
+/// How many rows like `row` fit on the terminal together with `table`'s header, a footer
+/// line and the prompt. `None` when stdout isn't a terminal.
+pub fn rows_fitting(table: &Table, row: Vec<Cell>) -> Option<usize> {
+    let (_, Height(height)) = terminal_size()?;
+    let mut probe = table.clone();
+    probe.add_row(row.clone());
+    let with_one = probe.lines().count();
+    probe.add_row(row);
+    let per_row = (probe.lines().count() - with_one).max(1);
+    let fixed = with_one - per_row + 2;
+    Some(((height as usize).saturating_sub(fixed) / per_row).max(1))
+}
+
+/// Title cell, `title` may be a highlighted version of the bookmark title.
+pub fn title_cell(bookmark: &Bookmark, title: &str) -> Cell {
+    if bookmark.status == Status::Pending {
+        Cell::new(title)
+            .fg(Color::White)
+            .add_attribute(Attribute::Bold)
+            .set_alignment(CellAlignment::Left)
+    } else {
+        Cell::new(title).set_alignment(CellAlignment::Left)
+    }
+}
+
+/// Url cell, a short clickable "LINK" for terminals that support OSC-8 hyperlinks or the
+/// whole url with `full`.
+pub fn url_cell(bookmark: &Bookmark, full: bool) -> Cell {
+    let Some(url) = &bookmark.url else {
+        return Cell::new("━━").fg(Color::DarkBlue);
+    };
+    if full {
+        Cell::new(url).fg(Color::DarkBlue).set_alignment(CellAlignment::Left)
+    } else {
+        Cell::new(Link::new("LINK", url)).fg(Color::DarkBlue)
+    }
+}
+
+/// Local date of a timestamp, "━━" when there is none.
+fn date_cell(at: Option<DateTime<Utc>>) -> Cell {
+    match at {
+        Some(at) => Cell::new(at.with_timezone(&Local).format("%Y-%m-%d")),
+        None => Cell::new("━━"),
+    }
+}
+
+pub fn category_cell(bookmark: &Bookmark, config: &Config) -> Cell {
+    Cell::new(&bookmark.category).fg(config.category_color(&bookmark.category))
+}
+
+fn priority_cell(bookmark: &Bookmark) -> Cell {
+    let Some(priority) = bookmark.priority else {
+        return Cell::new("━━");
+    };
+    let cell = Cell::new(priority);
+    match priority {
+        Priority::High => cell.fg(Color::Red).add_attribute(Attribute::Bold),
+        Priority::Medium => cell.fg(Color::Yellow),
+        Priority::Low => cell,
+    }
+}
+
+pub fn status_cell(bookmark: &Bookmark) -> Cell {
+    let cell = Cell::new(&bookmark.status);
+    match bookmark.status {
+        Status::Done => cell.fg(Color::Green),
+        Status::Pending => cell.add_attribute(Attribute::Italic),
+        Status::Reading => cell.fg(Color::Cyan),
+        Status::Paused => cell.fg(Color::Yellow),
+        Status::Abandoned | Status::Archived => cell.fg(Color::DarkGrey),
+        Status::None => cell,
+    }
+}
+
+/// Highlights the byte ranges `spans` of `text`. Only applies when stdout is a terminal,
+/// otherwise the escape codes would end up in pipes and files.
+pub fn highlight(text: &str, spans: &[Range<usize>]) -> String {
+    if spans.is_empty() || !std::io::stdout().is_terminal() {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for span in spans {
+        out.push_str(&text[last..span.start]);
+        out.push_str("\x1b[7m");
+        out.push_str(&text[span.clone()]);
+        out.push_str("\x1b[27m");
+        last = span.end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
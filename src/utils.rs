@@ -1,7 +1,12 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
 use cli_clipboard::{ClipboardContext, ClipboardProvider};
-use sublime_fuzzy::best_match;
 
-use crate::data::Bookmark;
 use crate::errors::{Error, Result};
 
 pub fn copy(text: String) -> Result<()> {
@@ -12,12 +17,40 @@ pub fn copy(text: String) -> Result<()> {
     Ok(())
 }
 
-pub fn fuzz<'a>(query: &str, store: &Vec<Bookmark>) -> usize {
-    let (id, _) = store
+/// Prints `message` to stderr, so it stays out of piped output, and reads a trimmed
+/// line from stdin.
+pub fn prompt(message: &str) -> Result<String> {
+    eprint!("{message}");
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Writes `data` to a temporary file next to `path` and renames it over `path`, so a
+/// crash midway leaves either the old or the new file but never a truncated one.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("arx");
+    let tmp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    Ok(result?)
+}
+
+/// The accepted values of a clap value enum, comma separated for error messages.
+pub fn value_names<T: ValueEnum>() -> String {
+    T::value_variants()
         .iter()
-        .filter_map(|i| best_match(query, &i.title))
-        .enumerate()
-        .max_by(|(_, a), (_, b)| a.score().cmp(&b.score()))
-        .unwrap();
-    id
+        .filter_map(|v| v.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
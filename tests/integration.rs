@@ -1,12 +1,22 @@
+use std::{fs, path::Path};
+
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+/// Runs arx with a config and bookmarks of its own in `dir`, away from the user's.
+fn arx(dir: &Path) -> Command {
+    let mut command = Command::cargo_bin("arx").expect("Failed to find arx binary");
+    command.env("ARX_CONFIG", dir.join("config.toml")).env("ARX_DATA", dir.join("data"));
+    command
+}
+
 #[test]
 fn test_cli_workflow() {
+    let dir = std::env::temp_dir().join(format!("arx-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
 
     // Test: Add a bookmark
-    let assert = Command::cargo_bin("arx")
-        .expect("Failed to find arx binary")
+    let assert = arx(&dir)
         .arg("add")
         .arg("The C Programming Language")
         .arg("--category")
@@ -17,8 +27,7 @@ fn test_cli_workflow() {
         .stdout(predicate::str::contains("Bookmark with ID #1 successfully added!"));
 
     // Test: List bookmarks
-    let assert = Command::cargo_bin("arx")
-        .expect("Failed to find arx binary")
+    let assert = arx(&dir)
         .arg("ls")
         .assert();
     assert
@@ -26,10 +35,755 @@ fn test_cli_workflow() {
         .stdout(predicate::str::contains("The C Programming Language"));
 
     // Test: Mark bookmark as done
-    let assert = Command::cargo_bin("arx")
-        .expect("Failed to find arx binary")
+    let assert = arx(&dir)
         .arg("done")
         .arg("1")
         .assert();
     assert.success();
+
+    // Test: IDs stay the same after removing another bookmark
+    let assert = arx(&dir)
+        .arg("add")
+        .arg("Crafting Interpreters")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Bookmark with ID #2 successfully added!"));
+
+    let assert = arx(&dir)
+        .arg("rm")
+        .arg("1")
+        .assert();
+    assert.success();
+
+    let assert = arx(&dir)
+        .arg("done")
+        .arg("2")
+        .assert();
+    assert.success();
+
+    // Test: A query without any match is an error instead of a panic
+    let assert = arx(&dir)
+        .arg("done")
+        .arg("zzzz")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("No bookmark matches 'zzzz'"));
+
+    // Test: An ambiguous query fails in strict mode
+    arx(&dir)
+        .arg("add")
+        .arg("Crafting Compilers")
+        .assert()
+        .success();
+    let assert = arx(&dir)
+        .arg("done")
+        .arg("crafting")
+        .arg("--strict")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("'crafting' matches 2 bookmarks"));
+
+    // Test: Search looks into notes too
+    arx(&dir)
+        .arg("add")
+        .arg("Writing an OS in Rust")
+        .arg("--notes")
+        .arg("covers paging and interrupts")
+        .assert()
+        .success();
+    let assert = arx(&dir)
+        .arg("search")
+        .arg("paging")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Writing an OS in Rust"))
+        .stdout(predicate::str::contains("Found 1 matching bookmark(s)"));
+
+    // Test: Import a browser export, skipping duplicate urls
+    let assert = arx(&dir)
+        .arg("import")
+        .arg("tests/fixtures/bookmarks.html")
+        .assert();
+    assert.success().stdout(predicate::str::contains(
+        "Imported 1 bookmark(s), skipped 1 already saved url(s)",
+    ));
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--tag")
+        .arg("reading-list")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("The Rustonomicon"));
+
+    // Test: Export a filtered subset to stdout
+    let assert = arx(&dir)
+        .arg("export")
+        .arg("--format")
+        .arg("md")
+        .arg("--tag")
+        .arg("reading-list")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(
+            "- [ ] [The Rustonomicon](<https://doc.rust-lang.org/nomicon/>) `reading-list`",
+        ))
+        .stdout(predicate::str::contains("Crafting").not());
+
+    // Test: Structured output for list and mutating commands
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::starts_with("["))
+        .stdout(predicate::str::contains(r#""title":"Crafting Compilers""#));
+    let assert = arx(&dir)
+        .arg("add")
+        .arg("Structure and Interpretation of Computer Programs")
+        .arg("--output")
+        .arg("jsonl")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""id":6"#))
+        .stdout(predicate::str::contains("successfully added").not());
+
+    // Test: Saves leave snapshots behind that can be restored
+    let assert = arx(&dir)
+        .arg("restore")
+        .arg("1")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Restored 4 bookmark(s) from the snapshot taken"));
+
+    // Test: Undo and redo the restore
+    let assert = arx(&dir)
+        .arg("undo")
+        .arg("-y")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Undo of `restore` done"));
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Structure and Interpretation"));
+    let assert = arx(&dir)
+        .arg("redo")
+        .arg("-y")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Redo of `restore` done"));
+    let assert = arx(&dir)
+        .arg("redo")
+        .arg("-y")
+        .assert();
+    assert.failure().stderr(predicate::str::contains("Nothing to redo"));
+
+    // Test: Edit applies every given field
+    let assert = arx(&dir)
+        .arg("edit")
+        .arg("4")
+        .arg("--title")
+        .arg("Writing an OS")
+        .arg("--url")
+        .arg("https://os.phil-opp.com")
+        .arg("--add-tag")
+        .arg("rust")
+        .arg("--output")
+        .arg("json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""title":"Writing an OS""#))
+        .stdout(predicate::str::contains(r#""url":"https://os.phil-opp.com""#))
+        .stdout(predicate::str::contains(r#""tags":["rust"]"#));
+    let assert = arx(&dir)
+        .arg("edit")
+        .arg("4")
+        .arg("--no-url")
+        .arg("--remove-tag")
+        .arg("rust")
+        .arg("--output")
+        .arg("json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""url":null"#))
+        .stdout(predicate::str::contains(r#""tags":null"#));
+
+    // Test: Interactive edit reads the bookmark back from the editor
+    let assert = arx(&dir)
+        .env("VISUAL", "sed -i s/Writing/Hacking/")
+        .arg("edit")
+        .arg("4")
+        .arg("--interactive")
+        .arg("--output")
+        .arg("json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""title":"Hacking an OS""#));
+    let assert = arx(&dir)
+        .env("VISUAL", "sed -i s/status.*/status=\"lost\"/")
+        .arg("edit")
+        .arg("4")
+        .arg("--interactive")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("unknown status 'lost'"))
+        .stderr(predicate::str::contains("Edit cancelled"));
+
+    // Test: Bulk commands work on ranges and filters
+    let assert = arx(&dir)
+        .arg("done")
+        .arg("--tag")
+        .arg("reading-list")
+        .arg("--dry-run")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("The Rustonomicon"))
+        .stdout(predicate::str::contains("1 bookmark(s) selected, nothing was changed"));
+    let assert = arx(&dir)
+        .arg("edit")
+        .arg("2-3")
+        .arg("--add-tag")
+        .arg("compilers")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Edited 2 bookmarks"));
+    let assert = arx(&dir)
+        .arg("done")
+        .arg("--tag")
+        .arg("compilers")
+        .arg("--output")
+        .arg("json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""title":"Crafting Compilers""#))
+        .stdout(predicate::str::contains(r#""status":"Done""#))
+        // already done, so filters leave it out
+        .stdout(predicate::str::contains("Crafting Interpreters").not());
+    let assert = arx(&dir)
+        .arg("done")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("Select bookmarks by ID, range, query"));
+
+    // Test: Filter expressions for list and export
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--where")
+        .arg("tag:compilers and (status:done or status:pending) and not title:*compilers*")
+        .arg("--json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""title":"Crafting Interpreters""#))
+        .stdout(predicate::str::contains("Crafting Compilers").not());
+    let assert = arx(&dir)
+        .arg("export")
+        .arg("--format")
+        .arg("csv")
+        .arg("--where")
+        .arg("url:*rust-lang.org* created:>2000-01-01")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("The Rustonomicon"))
+        .stdout(predicate::str::contains("Hacking an OS").not());
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--where")
+        .arg("tag:rust and (category:book")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("missing closing ')'"));
+
+    // Test: Sort the list
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--sort")
+        .arg("title")
+        .arg("--reverse")
+        .arg("--json")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("The Rustonomicon.*Hacking an OS").unwrap());
+
+    // Test: Pages only hold the rows of that page
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--page-by")
+        .arg("1")
+        .arg("-p")
+        .arg("2")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("The Rustonomicon"))
+        .stdout(predicate::str::contains("Hacking an OS").not())
+        .stdout(predicate::str::contains("Showing page 2 out of 2"));
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--no-page")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("The Rustonomicon"))
+        .stdout(predicate::str::contains("Hacking an OS"))
+        .stdout(predicate::str::contains("Showing page").not());
+
+    // Test: Urls are printed whole when not writing to a terminal
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("urls")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("https://doc.rust-lang.org/nomicon/"))
+        .stdout(predicate::str::contains("\x1b]8;;").not());
+
+    // Test: Choose the columns of the list
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--all")
+        .arg("--columns")
+        .arg("id,tags,created")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("created"))
+        .stdout(predicate::str::contains("compilers"))
+        .stdout(predicate::str::contains("Crafting").not());
+
+    // Test: Categories are checked against the configured ones, ignoring case
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--category")
+        .arg("podcast")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("possible values: book, article"));
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--all")
+        .arg("--category")
+        .arg("OTHER")
+        .assert();
+    assert.success();
+
+    // Test: Finished bookmarks remember when, reading ones when they were started
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let assert = arx(&dir)
+        .arg("edit")
+        .arg("4")
+        .arg("--set-status")
+        .arg("reading")
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--where")
+        .arg(format!("completed:>={today}"))
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Crafting Interpreters"))
+        .stdout(predicate::str::contains("Hacking an OS").not());
+    let assert = arx(&dir)
+        .arg("ls")
+        .arg("--status")
+        .arg("reading")
+        .arg("--columns")
+        .arg("title,started")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Hacking an OS"))
+        .stdout(predicate::str::contains(today));
+
+    // Test: Moving the store saves to the new location and brings the bookmarks back
+    let original = dir.join("data").join("bookmarks.json");
+    let moved = dir.join("moved").join("bookmarks.json");
+    let config = fs::read_to_string(dir.join("config.toml")).unwrap_or_default();
+    let mut lines = vec![format!("save_location = {original:?}")];
+    lines.extend(config.lines().filter(|l| !l.starts_with("save_location")).map(String::from));
+    fs::write(dir.join("config.toml"), lines.join("\n")).expect("Failed to write the config");
+    let assert = arx(&dir)
+        .env_remove("ARX_DATA")
+        .arg("config")
+        .arg("--save-location")
+        .arg(&moved)
+        .assert();
+    assert.success();
+    assert!(moved.exists() && !original.exists());
+    let assert = arx(&dir)
+        .env_remove("ARX_DATA")
+        .arg("add")
+        .arg("Moved along")
+        .assert();
+    assert.success();
+    assert!(fs::read_to_string(&moved).unwrap().contains("Moved along"));
+    fs::copy(&moved, &original).expect("Failed to copy the store back");
+    let assert = arx(&dir)
+        .env_remove("ARX_DATA")
+        .arg("config")
+        .arg("--save-location")
+        .arg(&original)
+        .arg("--on-existing")
+        .arg("abort")
+        .assert();
+    assert.failure().stderr(predicate::str::contains("nothing was moved"));
+    let assert = arx(&dir)
+        .env_remove("ARX_DATA")
+        .arg("config")
+        .arg("--save-location")
+        .arg(&original)
+        .arg("--on-existing")
+        .arg("overwrite")
+        .assert();
+    assert.success();
+    assert!(!moved.exists());
+    let assert = arx(&dir)
+        .arg("ls")
+        .assert();
+    assert.success().stdout(predicate::str::contains("Moved along"));
+
+    // Test: --data-dir wins over ARX_DATA and the save location can't be changed under it
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(dir.join("elsewhere"))
+        .arg("ls")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("You have no bookmarks yet"));
+    let assert = arx(&dir)
+        .arg("config")
+        .arg("--save-location")
+        .arg(&moved)
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("overridden by --data-dir or ARX_DATA"));
+
+    // Test: Profiles keep bookmarks of their own
+    let assert = arx(&dir)
+        .arg("profile")
+        .arg("create")
+        .arg("work")
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--profile")
+        .arg("work")
+        .arg("add")
+        .arg("Design doc")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Bookmark with ID #1 successfully added!"));
+    let assert = arx(&dir)
+        .arg("ls")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Moved along"))
+        .stdout(predicate::str::contains("Design doc").not());
+    let assert = arx(&dir)
+        .arg("profile")
+        .arg("list")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("default"))
+        .stdout(predicate::str::contains("work"));
+    let assert = arx(&dir)
+        .arg("profile")
+        .arg("delete")
+        .arg("work")
+        .arg("--yes")
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--profile")
+        .arg("work")
+        .arg("ls")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("No profile named `work`"));
+
+    // Test: Files from older versions are migrated after a backup, newer ones are refused
+    let legacy = dir.join("legacy");
+    fs::create_dir_all(&legacy).expect("Failed to create a data dir");
+    fs::write(
+        legacy.join("bookmarks.json"),
+        concat!(
+            r#"{"next_id":2,"bookmarks":[{"id":1,"title":"Old format","category":"Book","#,
+            r#""url":null,"tags":null,"notes":null,"status":"Pending","hidden":false,"#,
+            r#""created_at":"2025-01-01T00:00:00Z"}]}"#
+        ),
+    )
+    .expect("Failed to write the legacy store");
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&legacy)
+        .arg("ls")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Old format"))
+        .stdout(predicate::str::contains("book"));
+    assert!(legacy.join("bookmarks.json.v0.bak").exists());
+    fs::write(legacy.join("bookmarks.json"), r#"{"schema_version":999,"next_id":1,"bookmarks":[]}"#)
+        .expect("Failed to write the store");
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&legacy)
+        .arg("ls")
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("saved by a newer arx"));
+
+    // Test: Bookmarks can be moved to SQLite and keep working there
+    let shared = dir.join("shared");
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["add", "Shared doc", "-t", "team"])
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["migrate-storage", "--to", "sqlite"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("bookmarks.db"));
+    assert!(shared.join("bookmarks.db").exists());
+    assert!(!shared.join("bookmarks.json").exists());
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["done", "1"])
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["ls", "--all", "--columns", "id,title,tags,status"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Shared doc"))
+        .stdout(predicate::str::contains("team"))
+        .stdout(predicate::str::contains("DONE"));
+
+    // Test: Export includes done and hidden bookmarks unless the filters leave them out
+    let archive = dir.join("archive");
+    for args in [
+        vec!["add", "Finished paper"],
+        vec!["add", "Secret notes", "--hidden"],
+        vec!["done", "1"],
+    ] {
+        arx(&dir).arg("--data-dir").arg(&archive).args(args).assert().success();
+    }
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["export", "--format", "json"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Finished paper"))
+        .stdout(predicate::str::contains("Secret notes"));
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["export", "--format", "json", "--where", "not status:done"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Finished paper").not())
+        .stdout(predicate::str::contains("Secret notes"));
+
+    // Test: Edit sets the category and status with the long flags, selecting by other names
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["edit", "2", "--category", "book", "--status", "reading", "--json"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains(r#""category":"book""#))
+        .stdout(predicate::str::contains(r#""status":"Reading""#));
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["edit", "--in-category", "book", "--with-status", "reading", "--all"])
+        .args(["--set-notes", "x", "--dry-run"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Secret notes"));
+
+    // Test: The configured columns are the default ones of list
+    let assert = arx(&dir)
+        .args(["config", "--columns", "id,title,tags"])
+        .assert();
+    assert.success();
+    assert!(
+        fs::read_to_string(dir.join("config.toml"))
+            .expect("Failed to read the config")
+            .contains("columns")
+    );
+    let assert = arx(&dir)
+        .arg("ls")
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("tags"))
+        .stdout(predicate::str::contains("category").not())
+        .stdout(predicate::str::contains("status").not());
+
+    // Test: Categories in filter expressions are checked against the config
+    let assert = arx(&dir)
+        .args(["ls", "--where", "category:typo"])
+        .assert();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("Unknown category 'typo'"));
+
+    // Test: Archiving a done bookmark keeps its completion date
+    for args in [
+        vec!["add", "Read and shelved"],
+        vec!["done", "3"],
+        vec!["edit", "3", "-s", "archived"],
+    ] {
+        arx(&dir).arg("--data-dir").arg(&archive).args(args).assert().success();
+    }
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["ls", "--where", "completed:>=2020-01-01", "--columns", "id,title,completed"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Read and shelved"))
+        .stdout(predicate::str::contains(chrono::Local::now().format("%Y-%m-%d").to_string()));
+
+    // Test: Sort by priority and due date, bookmarks without them last
+    for args in [
+        vec!["add", "Someday", "--priority", "low", "--due", "2030-01-01"],
+        vec!["add", "Urgent", "--priority", "high"],
+        vec!["edit", "5", "--set-due", "2025-01-01"],
+    ] {
+        arx(&dir).arg("--data-dir").arg(&archive).args(args).assert().success();
+    }
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["ls", "--sort", "priority", "--all", "--json"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("Urgent.*Someday.*Finished paper").unwrap());
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["ls", "--sort", "due", "--columns", "title,priority,due"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match("(?s)Urgent.*high.*2025-01-01.*Someday.*low").unwrap());
+
+    // Test: Filter expressions select the same bookmarks from SQLite
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["add", "Shared sheet", "-t", "team", "--hidden"])
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&shared)
+        .args(["ls", "--where", "not status:done and (team or id:>5)", "--all", "--json"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Shared sheet"))
+        .stdout(predicate::str::contains("Shared doc").not());
+
+    // Test: A store of another kind than the configured one points to migrate-storage
+    let assert = arx(&dir)
+        .arg("ls")
+        .assert();
+    assert
+        .success()
+        .stderr(predicate::str::contains("arx migrate-storage --to sqlite"));
+
+    // Test: Values that look like the global flags are read as values
+    let assert = arx(&dir)
+        .args(["add", "Flag notes", "--notes=--config"])
+        .arg(format!("--data-dir={}", archive.display()))
+        .assert();
+    assert.success();
+    let assert = arx(&dir)
+        .arg("--data-dir")
+        .arg(&archive)
+        .args(["ls", "notes", "--where", "notes:--config"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::contains("Flag notes"));
+
+    // Test: Listing profiles counts their bookmarks without migrating them
+    let assert = arx(&dir)
+        .args(["profile", "create", "old"])
+        .assert();
+    assert.success();
+    let old_store = dir.join("data").join("profiles").join("old");
+    fs::create_dir_all(&old_store).expect("Failed to create a profile dir");
+    fs::write(
+        old_store.join("bookmarks.json"),
+        concat!(
+            r#"{"next_id":2,"bookmarks":[{"id":1,"title":"Old profile","category":"Book","#,
+            r#""url":null,"tags":null,"notes":null,"status":"Pending","hidden":false,"#,
+            r#""created_at":"2025-01-01T00:00:00Z"}]}"#
+        ),
+    )
+    .expect("Failed to write the profile store");
+    let assert = arx(&dir)
+        .args(["profile", "list"])
+        .assert();
+    assert
+        .success()
+        .stdout(predicate::str::is_match(r"old\s+┆ 1 ").unwrap());
+    assert!(!old_store.join("bookmarks.json.v0.bak").exists());
+
+    let _ = fs::remove_dir_all(dir);
 }